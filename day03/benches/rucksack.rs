@@ -0,0 +1,72 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use day03::{badge_weight, item_weight, shared_weight};
+use itertools::Itertools;
+use std::collections::HashSet;
+
+const ITEMS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+fn generate(lines: usize, len: usize) -> String {
+    let mut seed = 0x2545_f491_4f6c_dd1du64;
+    let mut next = move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        ITEMS[(seed % ITEMS.len() as u64) as usize] as char
+    };
+    (0..lines)
+        .map(|_| (0..len).map(|_| next()).collect::<String>())
+        .join("\n")
+}
+
+fn hashset_shared(input: &str) -> u32 {
+    input
+        .lines()
+        .map(|l| {
+            let (l, r) = l.split_at(l.len() / 2);
+            let h1: HashSet<char> = l.chars().collect();
+            let h2: HashSet<char> = r.chars().collect();
+            h1.intersection(&h2).fold(0, |acc, c| acc + item_weight(*c))
+        })
+        .sum()
+}
+
+fn hashset_badges(input: &str) -> u32 {
+    input
+        .lines()
+        .chunks(3)
+        .into_iter()
+        .map(|c| {
+            c.map(|r| HashSet::from_iter(r.chars()))
+                .reduce(|acc: HashSet<char>, v| acc.intersection(&v).cloned().collect())
+                .unwrap()
+                .iter()
+                .fold(0, |acc, v| acc + item_weight(*v))
+        })
+        .sum()
+}
+
+fn bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("rucksack");
+    for lines in [3_000, 30_000, 300_000] {
+        let input = generate(lines, 48);
+        assert_eq!(hashset_shared(&input), shared_weight(&input));
+        assert_eq!(hashset_badges(&input), badge_weight(&input, 3));
+
+        group.bench_with_input(BenchmarkId::new("shared/hashset", lines), &input, |b, i| {
+            b.iter(|| hashset_shared(black_box(i)))
+        });
+        group.bench_with_input(BenchmarkId::new("shared/bitmask", lines), &input, |b, i| {
+            b.iter(|| shared_weight(black_box(i)))
+        });
+        group.bench_with_input(BenchmarkId::new("badges/hashset", lines), &input, |b, i| {
+            b.iter(|| hashset_badges(black_box(i)))
+        });
+        group.bench_with_input(BenchmarkId::new("badges/bitmask", lines), &input, |b, i| {
+            b.iter(|| badge_weight(black_box(i), 3))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
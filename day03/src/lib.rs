@@ -0,0 +1,135 @@
+pub mod validate;
+
+use itertools::Itertools;
+
+/// Items packed into a 52-bit mask: bit 0 is `a`, bit 25 is `z`, bit 26 is `A`, bit 51 is `Z`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Rucksack(u64);
+
+pub fn item_bit(item: char) -> Option<u32> {
+    match item {
+        'a'..='z' => Some(item as u32 - 'a' as u32),
+        'A'..='Z' => Some(item as u32 - 'A' as u32 + 26),
+        _ => None,
+    }
+}
+
+pub fn item_weight(item: char) -> u32 {
+    item_bit(item).map(|b| b + 1).unwrap_or(0)
+}
+
+impl Rucksack {
+    pub const ALL: Rucksack = Rucksack((1 << 52) - 1);
+
+    pub fn from_items(items: &str) -> Self {
+        Rucksack(
+            items
+                .chars()
+                .filter_map(item_bit)
+                .fold(0, |acc, b| acc | 1 << b),
+        )
+    }
+
+    pub fn compartments(line: &str) -> (Self, Self) {
+        let (l, r) = line.split_at(line.len() / 2);
+        (Self::from_items(l), Self::from_items(r))
+    }
+
+    pub fn intersect(self, other: Self) -> Self {
+        Rucksack(self.0 & other.0)
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn len(self) -> u32 {
+        self.0.count_ones()
+    }
+
+    pub fn contains(self, item: char) -> bool {
+        item_bit(item).is_some_and(|b| self.0 & 1 << b != 0)
+    }
+
+    pub fn items(self) -> impl Iterator<Item = char> {
+        ('a'..='z')
+            .chain('A'..='Z')
+            .enumerate()
+            .filter_map(move |(i, c)| (self.0 & 1 << i != 0).then_some(c))
+    }
+
+    /// Sum of the weights of all items, each weight being the item's bit position + 1.
+    pub fn weight(self) -> u32 {
+        let mut mask = self.0;
+        let mut total = 0;
+        while mask != 0 {
+            total += mask.trailing_zeros() + 1;
+            mask &= mask - 1;
+        }
+        total
+    }
+}
+
+pub fn shared_items(line: &str) -> Rucksack {
+    let (l, r) = Rucksack::compartments(line);
+    l.intersect(r)
+}
+
+/// Items common to every rucksack of the group, whatever its size.
+pub fn group_badge<'a>(group: impl IntoIterator<Item = &'a str>) -> Rucksack {
+    group
+        .into_iter()
+        .map(Rucksack::from_items)
+        .fold(Rucksack::ALL, Rucksack::intersect)
+}
+
+pub fn shared_weight(input: &str) -> u32 {
+    input.lines().map(|l| shared_items(l).weight()).sum()
+}
+
+/// Sum of the badge weights over consecutive groups of `group_size` lines, the last group
+/// taking whatever lines are left. Panics if `group_size` is 0.
+pub fn badge_weight(input: &str, group_size: usize) -> u32 {
+    assert!(group_size > 0, "a group needs at least one rucksack");
+    input
+        .lines()
+        .chunks(group_size)
+        .into_iter()
+        .map(|g| group_badge(g).weight())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_item_weight() {
+        assert_eq!(item_weight('a'), 1);
+        assert_eq!(item_weight('z'), 26);
+        assert_eq!(item_weight('A'), 27);
+        assert_eq!(item_weight('Z'), 52);
+        assert_eq!(item_weight('1'), 0);
+    }
+
+    #[test]
+    fn test_shared_items() {
+        let shared = shared_items("vJrwpWtwJgWrhcsFMMfFFhFp");
+        assert_eq!(shared.items().collect::<String>(), "p");
+        assert_eq!(shared.weight(), 16);
+    }
+
+    #[test]
+    #[should_panic(expected = "a group needs at least one rucksack")]
+    fn test_empty_groups() {
+        badge_weight("abab", 0);
+    }
+
+    #[test]
+    fn test_group_sizes() {
+        let lines = ["abcX", "bcdX", "cdeX", "cxyX"];
+        assert_eq!(group_badge(lines).items().collect::<String>(), "cX");
+        assert_eq!(group_badge(lines[..2].iter().copied()).len(), 3);
+        assert_eq!(badge_weight(&lines.join("\n"), 2), 2 + 3 + 50 + 3 + 50);
+    }
+}
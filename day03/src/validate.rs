@@ -0,0 +1,347 @@
+use crate::{group_badge, item_bit, shared_items, Rucksack};
+use itertools::Itertools;
+use std::fmt;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Issue {
+    OddLength(usize),
+    InvalidItem {
+        item: char,
+        column: usize,
+    },
+    SharedItems(Rucksack),
+    /// No arrangement of the items splits into two halves with nothing in common.
+    Unsplittable,
+}
+
+/// Swap the item at `columns.0`, in the first compartment, with the one at `columns.1`, in
+/// the second. Columns count from 1.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Swap {
+    pub items: (char, char),
+    pub columns: (usize, usize),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RucksackReport {
+    pub line: usize,
+    pub misplaced: Rucksack,
+    pub issues: Vec<Issue>,
+    pub swaps: Vec<Swap>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GroupReport {
+    pub lines: (usize, usize),
+    pub badges: Rucksack,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Report {
+    pub rucksacks: Vec<RucksackReport>,
+    pub groups: Vec<GroupReport>,
+}
+
+impl Report {
+    pub fn is_valid(&self) -> bool {
+        self.rucksacks.iter().all(|r| r.issues.is_empty()) && self.groups.is_empty()
+    }
+
+    pub fn swaps(&self) -> usize {
+        self.rucksacks.iter().map(|r| r.swaps.len()).sum()
+    }
+}
+
+/// The fewest swaps leaving no item in both compartments, or `None` if there are none.
+///
+/// Every type of item ends up in one compartment, and a swap puts two copies in place, so the
+/// plan is the cheapest choice of types for the first compartment adding up to exactly half
+/// the items, found by a knapsack over the types.
+fn plan_swaps(line: &str) -> Option<Vec<Swap>> {
+    let items = line.chars().collect_vec();
+    if !items.len().is_multiple_of(2) {
+        return None;
+    }
+    let half = items.len() / 2;
+    let types = items.iter().copied().unique().collect_vec();
+    let counts = types
+        .iter()
+        .map(|t| {
+            let first = items[..half].iter().filter(|c| *c == t).count();
+            (first, items.iter().filter(|c| *c == t).count() - first)
+        })
+        .collect_vec();
+
+    // cost[k][size]: copies out of place once the first `k` types are placed, `size` items
+    // having gone into the first compartment.
+    let mut cost = vec![vec![None; half + 1]; types.len() + 1];
+    cost[0][0] = Some(0);
+    for (k, (first, second)) in counts.iter().enumerate() {
+        for size in 0..=half {
+            let Some(c) = cost[k][size] else { continue };
+            let to_second = &mut cost[k + 1][size];
+            *to_second = Some(to_second.map_or(c + first, |s: usize| s.min(c + first)));
+            if size + first + second <= half {
+                let to_first = &mut cost[k + 1][size + first + second];
+                *to_first = Some(to_first.map_or(c + second, |g: usize| g.min(c + second)));
+            }
+        }
+    }
+    cost[types.len()][half]?;
+
+    let mut in_first = vec![false; types.len()];
+    let mut size = half;
+    for k in (0..types.len()).rev() {
+        let (first, second) = counts[k];
+        let c = cost[k + 1][size].unwrap();
+        if size >= first + second && cost[k][size - first - second].map(|p| p + second) == Some(c) {
+            in_first[k] = true;
+            size -= first + second;
+        }
+    }
+
+    let belongs_first = |c: &char| in_first[types.iter().position(|t| t == c).unwrap()];
+    let out_of_first = (0..half).filter(|i| !belongs_first(&items[*i]));
+    let out_of_second = (half..items.len()).filter(|i| belongs_first(&items[*i]));
+    Some(
+        out_of_first
+            .zip(out_of_second)
+            .map(|(i, j)| Swap {
+                items: (items[i], items[j]),
+                columns: (i + 1, j + 1),
+            })
+            .collect(),
+    )
+}
+
+fn check_rucksack(line: usize, items: &str) -> RucksackReport {
+    let mut issues = items
+        .chars()
+        .enumerate()
+        .filter(|(_, c)| item_bit(*c).is_none())
+        .map(|(i, item)| Issue::InvalidItem {
+            item,
+            column: i + 1,
+        })
+        .collect_vec();
+    let len = items.chars().count();
+    if !len.is_multiple_of(2) {
+        issues.insert(0, Issue::OddLength(len));
+    }
+
+    let misplaced = match items.is_char_boundary(items.len() / 2) {
+        true => shared_items(items),
+        false => Rucksack::default(),
+    };
+    if misplaced.len() != 1 {
+        issues.push(Issue::SharedItems(misplaced));
+    }
+    let swaps = match misplaced.is_empty() || !len.is_multiple_of(2) {
+        true => vec![],
+        false => plan_swaps(items).unwrap_or_else(|| {
+            issues.push(Issue::Unsplittable);
+            vec![]
+        }),
+    };
+
+    RucksackReport {
+        line,
+        misplaced,
+        issues,
+        swaps,
+    }
+}
+
+/// Checks every rucksack, and every group of `group_size` lines. Panics if `group_size` is 0.
+pub fn validate(input: &str, group_size: usize) -> Report {
+    assert!(group_size > 0, "a group needs at least one rucksack");
+    let lines = input.lines().collect_vec();
+    let rucksacks = lines
+        .iter()
+        .enumerate()
+        .map(|(i, l)| check_rucksack(i + 1, l))
+        .collect();
+    let groups = lines
+        .chunks(group_size)
+        .enumerate()
+        .map(|(i, g)| GroupReport {
+            lines: (i * group_size + 1, i * group_size + g.len()),
+            badges: group_badge(g.iter().copied()),
+        })
+        .filter(|g| g.badges.len() != 1 || g.lines.1 - g.lines.0 + 1 != group_size)
+        .collect();
+
+    Report { rucksacks, groups }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Issue::OddLength(len) => write!(f, "odd number of items ({})", len),
+            Issue::InvalidItem { item, column } => {
+                write!(f, "invalid item {:?} at column {}", item, column)
+            }
+            Issue::SharedItems(s) if s.is_empty() => write!(f, "no item in both compartments"),
+            Issue::SharedItems(s) => write!(
+                f,
+                "{} items in both compartments: {}",
+                s.len(),
+                s.items().collect::<String>()
+            ),
+            Issue::Unsplittable => write!(f, "no way to split the items into disjoint halves"),
+        }
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for r in &self.rucksacks {
+            if !r.misplaced.is_empty() {
+                writeln!(
+                    f,
+                    "line {}: misplaced {}",
+                    r.line,
+                    r.misplaced.items().collect::<String>()
+                )?;
+            }
+            for issue in &r.issues {
+                writeln!(f, "line {}: {}", r.line, issue)?;
+            }
+            for s in &r.swaps {
+                writeln!(
+                    f,
+                    "line {}: swap {} at column {} with {} at column {}",
+                    r.line, s.items.0, s.columns.0, s.items.1, s.columns.1
+                )?;
+            }
+        }
+        for g in &self.groups {
+            writeln!(
+                f,
+                "lines {}-{}: {} badges ({})",
+                g.lines.0,
+                g.lines.1,
+                g.badges.len(),
+                g.badges.items().collect::<String>()
+            )?;
+        }
+        write!(f, "{} swaps needed", self.swaps())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    static INPUT: &str = "vJrwpWtwJgWrhcsFMMfFFhFp
+jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
+PmmdzqPrVvPwwTWBwg
+wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
+ttgJtRGJQctTZtZT
+CrZsJsPPZsGzwwsLwLmpwMDw";
+
+    #[test]
+    fn test_valid_input() {
+        let report = validate(INPUT, 3);
+        assert!(report.is_valid());
+        assert_eq!(report.rucksacks[0].misplaced, Rucksack::from_items("p"));
+        assert_eq!(
+            report.rucksacks[0].swaps,
+            vec![Swap {
+                items: ('p', 'f'),
+                columns: (5, 19)
+            }]
+        );
+        for (line, r) in INPUT.lines().zip(&report.rucksacks) {
+            assert_eq!(shared_items(&apply(line, &r.swaps)), Rucksack::default());
+        }
+        assert_eq!(report.swaps(), 9);
+    }
+
+    fn apply(line: &str, swaps: &[Swap]) -> String {
+        let mut items = line.chars().collect_vec();
+        for s in swaps {
+            assert_eq!((items[s.columns.0 - 1], items[s.columns.1 - 1]), s.items);
+            assert!(s.columns.0 <= items.len() / 2 && s.columns.1 > items.len() / 2);
+            items.swap(s.columns.0 - 1, s.columns.1 - 1);
+        }
+        items.into_iter().collect()
+    }
+
+    /// Fewest swaps across the middle leaving the compartments disjoint, by breadth-first
+    /// search.
+    fn fewest_swaps(line: &str) -> Option<usize> {
+        let half = line.len() / 2;
+        let mut seen = HashSet::from([line.to_string()]);
+        let mut frontier = vec![line.to_string()];
+        for depth in 0.. {
+            if frontier.is_empty() {
+                return None;
+            }
+            if frontier.iter().any(|l| shared_items(l).is_empty()) {
+                return Some(depth);
+            }
+            frontier = frontier
+                .iter()
+                .flat_map(|l| {
+                    (0..half)
+                        .cartesian_product(half..l.len())
+                        .map(move |(i, j)| {
+                            let mut items = l.clone().into_bytes();
+                            items.swap(i, j);
+                            String::from_utf8(items).unwrap()
+                        })
+                })
+                .filter(|l| seen.insert(l.clone()))
+                .collect();
+        }
+        unreachable!()
+    }
+
+    #[test]
+    fn test_swaps_are_minimal() {
+        for line in [
+            "abcabd", "aabbab", "abcdcdab", "aaabbbab", "abacbcdd", "aaab", "abca",
+        ] {
+            let report = validate(line, 1);
+            let swaps = &report.rucksacks[0].swaps;
+            match fewest_swaps(line) {
+                Some(fewest) => {
+                    assert_eq!(swaps.len(), fewest, "{}", line);
+                    assert!(shared_items(&apply(line, swaps)).is_empty(), "{}", line);
+                }
+                None => assert!(report.rucksacks[0].issues.contains(&Issue::Unsplittable)),
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let report = validate("abcabd\nab1xy\nxyzw", 3);
+        assert!(!report.is_valid());
+        assert_eq!(
+            report.rucksacks[0].issues,
+            vec![Issue::SharedItems(Rucksack::from_items("ab"))]
+        );
+        assert_eq!(
+            report.rucksacks[1].issues,
+            vec![
+                Issue::OddLength(5),
+                Issue::InvalidItem {
+                    item: '1',
+                    column: 3
+                },
+                Issue::SharedItems(Rucksack::default())
+            ]
+        );
+        assert_eq!(
+            report.groups,
+            vec![GroupReport {
+                lines: (1, 3),
+                badges: Rucksack::default()
+            }]
+        );
+        assert_eq!(report.swaps(), 1);
+    }
+}
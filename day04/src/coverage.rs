@@ -0,0 +1,197 @@
+use crate::SectionRange;
+use itertools::Itertools;
+use std::{collections::BTreeSet, fmt};
+
+/// Assignment count per section, as disjoint ranges of constant depth in ascending order.
+/// Sections no range touches are left out.
+pub fn depth_profile<'a>(
+    ranges: impl IntoIterator<Item = &'a SectionRange>,
+) -> Vec<(SectionRange, usize)> {
+    let events = ranges
+        .into_iter()
+        .flat_map(|r| [(r.start as u64, 1i64), (r.end as u64 + 1, -1)])
+        .sorted_unstable()
+        .collect_vec();
+
+    let mut profile = Vec::new();
+    let mut depth = 0;
+    for (i, (pos, delta)) in events.iter().enumerate() {
+        depth += delta;
+        match events.get(i + 1) {
+            Some((next, _)) if next > pos && depth > 0 => profile.push((
+                SectionRange::new(*pos as u32, (next - 1) as u32),
+                depth as usize,
+            )),
+            _ => {}
+        }
+    }
+    profile
+}
+
+pub(crate) fn merge(ranges: impl IntoIterator<Item = SectionRange>) -> Vec<SectionRange> {
+    ranges.into_iter().fold(Vec::new(), |mut acc, r| {
+        match acc.last().and_then(|last: &SectionRange| last.union(&r)) {
+            Some(merged) => *acc.last_mut().unwrap() = merged,
+            None => acc.push(r),
+        }
+        acc
+    })
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Coverage {
+    /// From the lowest to the highest assigned section.
+    pub span: Option<SectionRange>,
+    /// Gaps inside `span` nobody is assigned to.
+    pub uncovered: Vec<SectionRange>,
+    pub max_depth: usize,
+    pub most_assigned: Vec<SectionRange>,
+}
+
+pub fn coverage(lines: &[Vec<SectionRange>]) -> Coverage {
+    let profile = depth_profile(lines.iter().flatten());
+    let (first, last) = match (profile.first(), profile.last()) {
+        (Some(first), Some(last)) => (first.0, last.0),
+        _ => return Coverage::default(),
+    };
+
+    let uncovered = profile
+        .iter()
+        .tuple_windows()
+        .filter(|((a, _), (b, _))| a.end + 1 < b.start)
+        .map(|((a, _), (b, _))| SectionRange::new(a.end + 1, b.start - 1))
+        .collect();
+    let max_depth = profile.iter().map(|(_, d)| *d).max().unwrap();
+    let most_assigned = merge(
+        profile
+            .iter()
+            .filter(|(_, d)| *d == max_depth)
+            .map(|(r, _)| *r),
+    );
+
+    Coverage {
+        span: Some(SectionRange::new(first.start, last.end)),
+        uncovered,
+        max_depth,
+        most_assigned,
+    }
+}
+
+/// Every pair of distinct lines `(i, j)`, `i < j`, with at least one section in common.
+///
+/// Sweeps the ranges by start keeping the ones still open by end, so that closing them costs
+/// O(log n) each; every open range overlaps the one being added, so the cost is O(n log n)
+/// plus the number of overlaps found.
+pub fn overlapping_lines(lines: &[Vec<SectionRange>]) -> Vec<(usize, usize)> {
+    let ranges = lines
+        .iter()
+        .enumerate()
+        .flat_map(|(i, l)| {
+            merge(l.iter().copied().sorted())
+                .into_iter()
+                .map(move |r| (r, i))
+        })
+        .sorted_unstable()
+        .collect_vec();
+
+    let mut open: BTreeSet<(u32, usize)> = BTreeSet::new();
+    let mut pairs = Vec::new();
+    for (range, line) in ranges {
+        while open.first().is_some_and(|(end, _)| *end < range.start) {
+            open.pop_first();
+        }
+        pairs.extend(
+            open.iter()
+                .filter(|(_, l)| *l != line)
+                .map(|(_, l)| (*l.min(&line), *l.max(&line))),
+        );
+        open.insert((range.end, line));
+    }
+    pairs.sort_unstable();
+    pairs.dedup();
+    pairs
+}
+
+impl fmt::Display for Coverage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let span = match self.span {
+            Some(span) => span,
+            None => return write!(f, "No sections assigned"),
+        };
+        writeln!(f, "Assigned span: {}", span)?;
+        writeln!(
+            f,
+            "Uncovered: {} sections in [{}]",
+            self.uncovered.iter().map(SectionRange::len).sum::<u32>(),
+            self.uncovered.iter().join(", ")
+        )?;
+        write!(
+            f,
+            "Most assigned: {} elves on [{}]",
+            self.max_depth,
+            self.most_assigned.iter().join(", ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_input;
+
+    static INPUT: &str = "2-4,6-8
+2-3,4-5
+2-3,4-5
+5-7,7-9
+2-8,3-7
+6-6,4-6
+2-6,4-8";
+
+    #[test]
+    fn test_coverage() {
+        let lines = parse_input("1-3,10-12\n2-4,11-11\n20-25").unwrap();
+        assert_eq!(
+            coverage(&lines),
+            Coverage {
+                span: Some(SectionRange::new(1, 25)),
+                uncovered: vec![SectionRange::new(5, 9), SectionRange::new(13, 19)],
+                max_depth: 2,
+                most_assigned: vec![SectionRange::new(2, 3), SectionRange::new(11, 11)],
+            }
+        );
+        assert_eq!(coverage(&[]), Coverage::default());
+    }
+
+    #[test]
+    fn test_overlapping_lines() {
+        let lines = parse_input(INPUT).unwrap();
+        let brute = (0..lines.len())
+            .tuple_combinations()
+            .filter(|(i, j)| {
+                lines[*i]
+                    .iter()
+                    .cartesian_product(&lines[*j])
+                    .any(|(a, b)| a.overlaps(b))
+            })
+            .collect_vec();
+        assert_eq!(overlapping_lines(&lines), brute);
+
+        let lines = parse_input("1-3,10-12\n2-4,11-11\n20-25\n4-4").unwrap();
+        assert_eq!(overlapping_lines(&lines), vec![(0, 1), (1, 3)]);
+    }
+
+    #[test]
+    fn test_many_lines() {
+        let n = 300_000;
+        let input = (0..n).map(|i| format!("{}-{}", i, i + 2)).join("\n");
+        let lines = parse_input(&input).unwrap();
+        let pairs = overlapping_lines(&lines);
+        assert_eq!(pairs.len(), 2 * n - 3);
+        assert_eq!(pairs[..3], [(0, 1), (0, 2), (1, 2)]);
+
+        let nested = (0..1000).map(|i| format!("{}-{}", i, 2000 - i));
+        let disjoint = (0..n).map(|i| format!("{}-{}", 3000 + 2 * i, 3000 + 2 * i));
+        let lines = parse_input(&nested.chain(disjoint).join("\n")).unwrap();
+        assert_eq!(overlapping_lines(&lines).len(), 1000 * 999 / 2);
+    }
+}
@@ -0,0 +1,147 @@
+pub mod coverage;
+
+use std::{fmt, str::FromStr};
+
+/// An inclusive range of section ids, e.g. `2-4`.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct SectionRange {
+    pub start: u32,
+    pub end: u32,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseRangeError(pub String);
+
+impl fmt::Display for ParseRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid section range {:?}", self.0)
+    }
+}
+
+impl std::error::Error for ParseRangeError {}
+
+impl SectionRange {
+    pub fn new(start: u32, end: u32) -> Self {
+        assert!(start <= end, "empty section range {}-{}", start, end);
+        SectionRange { start, end }
+    }
+
+    pub fn len(&self) -> u32 {
+        self.end - self.start + 1
+    }
+
+    /// Ranges are inclusive, so they always hold at least one section.
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    pub fn contains_section(&self, section: u32) -> bool {
+        self.start <= section && section <= self.end
+    }
+
+    pub fn contains(&self, other: &SectionRange) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+
+    pub fn overlaps(&self, other: &SectionRange) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+
+    pub fn intersection(&self, other: &SectionRange) -> Option<SectionRange> {
+        self.overlaps(other)
+            .then(|| SectionRange::new(self.start.max(other.start), self.end.min(other.end)))
+    }
+
+    /// The single range covering both, if they overlap or touch.
+    pub fn union(&self, other: &SectionRange) -> Option<SectionRange> {
+        (self.start <= other.end.saturating_add(1) && other.start <= self.end.saturating_add(1))
+            .then(|| SectionRange::new(self.start.min(other.start), self.end.max(other.end)))
+    }
+}
+
+impl FromStr for SectionRange {
+    type Err = ParseRangeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseRangeError(s.to_string());
+        let (start, end) = s.trim().split_once('-').ok_or_else(err)?;
+        let start = start.parse().map_err(|_| err())?;
+        let end = end.parse().map_err(|_| err())?;
+        match start <= end {
+            true => Ok(SectionRange { start, end }),
+            false => Err(err()),
+        }
+    }
+}
+
+impl fmt::Display for SectionRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
+/// Parses one line of comma separated assignments, any number of elves per line.
+pub fn parse_line(line: &str) -> Result<Vec<SectionRange>, ParseRangeError> {
+    line.split(',').map(str::parse).collect()
+}
+
+pub fn parse_input(input: &str) -> Result<Vec<Vec<SectionRange>>, ParseRangeError> {
+    input
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(parse_line)
+        .collect()
+}
+
+/// The sections covered by at least `min` of the given ranges, merged into disjoint ranges.
+pub fn covered_by_at_least(ranges: &[SectionRange], min: usize) -> Vec<SectionRange> {
+    coverage::merge(
+        coverage::depth_profile(ranges)
+            .into_iter()
+            .filter(|(_, depth)| *depth >= min)
+            .map(|(r, _)| r),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn r(s: &str) -> SectionRange {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_range_algebra() {
+        assert_eq!(r("2-8").len(), 7);
+        assert!(r("2-8").contains(&r("3-7")));
+        assert!(!r("3-7").contains(&r("2-8")));
+        assert!(r("5-7").overlaps(&r("7-9")));
+        assert!(!r("2-4").overlaps(&r("6-8")));
+        assert_eq!(r("5-7").intersection(&r("7-9")), Some(r("7-7")));
+        assert_eq!(r("2-4").intersection(&r("6-8")), None);
+        assert_eq!(r("2-4").union(&r("5-8")), Some(r("2-8")));
+        assert_eq!(r("2-4").union(&r("6-8")), None);
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse_line("1-2,3-4,5-6"),
+            Ok(vec![r("1-2"), r("3-4"), r("5-6")])
+        );
+        assert_eq!(
+            parse_line("1-2,4-3"),
+            Err(ParseRangeError("4-3".to_string()))
+        );
+        assert!(parse_line("1-2;3-4").is_err());
+    }
+
+    #[test]
+    fn test_covered_by_at_least() {
+        let line = parse_line("1-5,3-8,4-6,10-12,11-11").unwrap();
+        assert_eq!(covered_by_at_least(&line, 3), vec![r("4-5")]);
+        assert_eq!(covered_by_at_least(&line, 2), vec![r("3-6"), r("11-11")]);
+        assert_eq!(covered_by_at_least(&line, 1), vec![r("1-8"), r("10-12")]);
+    }
+}
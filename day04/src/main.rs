@@ -1,39 +1,40 @@
+use day04::{
+    coverage::{coverage, overlapping_lines},
+    parse_input, SectionRange,
+};
 use itertools::Itertools;
-fn parse_input(input: &str) -> Vec<(u32, u32, u32, u32)> {
-    input
-        .lines()
-        .map(|l| {
-            l.split(&['-', ','][..])
-                .map(|t| t.parse::<u32>().unwrap())
-                .collect_tuple()
-                .unwrap()
-        })
-        .collect()
-}
 
-fn part1(pairs: &Vec<(u32, u32, u32, u32)>) -> u32 {
-    pairs.iter().fold(0, |acc, v| {
-        match (v.0 <= v.2 && v.1 >= v.3) || (v.0 >= v.2 && v.1 <= v.3) {
-            true => acc + 1,
-            false => acc,
-        }
-    })
+fn part1(lines: &[Vec<SectionRange>]) -> usize {
+    lines
+        .iter()
+        .filter(|l| {
+            l.iter()
+                .tuple_combinations()
+                .any(|(a, b)| a.contains(b) || b.contains(a))
+        })
+        .count()
 }
 
-fn part2(pairs: &Vec<(u32, u32, u32, u32)>) -> u32 {
-    pairs
+fn part2(lines: &[Vec<SectionRange>]) -> usize {
+    lines
         .iter()
-        .fold(0, |acc, v| match (v.1 < v.2) || (v.3 < v.0) {
-            true => acc,
-            false => acc + 1,
-        })
+        .filter(|l| l.iter().tuple_combinations().any(|(a, b)| a.overlaps(b)))
+        .count()
 }
 
 fn main() {
     let input = include_str!("input");
-    let pairs = parse_input(input);
+    let pairs = parse_input(input).unwrap();
     println!("Part1: {}", part1(&pairs));
     println!("Part2: {}", part2(&pairs));
+
+    if std::env::args().any(|a| a == "--coverage") {
+        println!("{}", coverage(&pairs));
+        println!(
+            "Overlapping line pairs: {}",
+            overlapping_lines(&pairs).len()
+        );
+    }
 }
 
 #[cfg(test)]
@@ -50,11 +51,11 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1(&parse_input(INPUT)), 2);
+        assert_eq!(part1(&parse_input(INPUT).unwrap()), 2);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2(&parse_input(INPUT)), 4);
+        assert_eq!(part2(&parse_input(INPUT).unwrap()), 4);
     }
 }
@@ -0,0 +1,136 @@
+use crate::{Move, Stacks};
+use std::fmt;
+
+pub trait Crane {
+    /// Most crates the crane can lift at once.
+    fn capacity(&self) -> usize;
+
+    /// Moves the top `count` crates of `from` onto `to`, lifting up to `capacity` at a time.
+    /// Each lift keeps the order of the crates it carries.
+    fn transfer(&self, stacks: &mut [Vec<char>], from: usize, to: usize, count: usize) {
+        let mut left = count;
+        while left > 0 {
+            let lift = left.min(self.capacity());
+            let at = stacks[from].len() - lift;
+            let crates = stacks[from].drain(at..).collect::<Vec<_>>();
+            stacks[to].extend(crates);
+            left -= lift;
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct CrateMover9000;
+
+#[derive(Clone, Copy, Debug)]
+pub struct CrateMover9001;
+
+/// A crane moving at most K crates at once.
+#[derive(Clone, Copy, Debug)]
+pub struct LimitedCrane(usize);
+
+impl LimitedCrane {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "a crane must lift at least one crate");
+        LimitedCrane(capacity)
+    }
+}
+
+impl Crane for CrateMover9000 {
+    fn capacity(&self) -> usize {
+        1
+    }
+}
+
+impl Crane for CrateMover9001 {
+    fn capacity(&self) -> usize {
+        usize::MAX
+    }
+}
+
+impl Crane for LimitedCrane {
+    fn capacity(&self) -> usize {
+        self.0
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MoveError {
+    /// 1-based position of the instruction in the list.
+    pub step: usize,
+    pub instruction: String,
+    pub available: usize,
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "step {}: {:?} but the stack only holds {} crates",
+            self.step, self.instruction, self.available
+        )
+    }
+}
+
+impl std::error::Error for MoveError {}
+
+pub fn run(stacks: &Stacks, moves: &[Move], crane: &impl Crane) -> Result<Stacks, MoveError> {
+    let mut stacks = stacks.clone();
+    for (i, m) in moves.iter().enumerate() {
+        stacks.apply(crane, m, i + 1)?;
+    }
+    Ok(stacks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stacks(crates: &[&str]) -> Stacks {
+        Stacks {
+            labels: (1..=crates.len()).map(|i| i.to_string()).collect(),
+            crates: crates.iter().map(|c| c.chars().collect()).collect(),
+        }
+    }
+
+    fn unload(crane: &impl Crane) -> String {
+        let m = Move {
+            count: 5,
+            from: 0,
+            to: 1,
+        };
+        let result = run(&stacks(&["ABCDE", ""]), &[m], crane).unwrap();
+        result.crates[1].iter().collect()
+    }
+
+    #[test]
+    fn test_cranes() {
+        assert_eq!(unload(&CrateMover9000), "EDCBA");
+        assert_eq!(unload(&CrateMover9001), "ABCDE");
+        assert_eq!(unload(&LimitedCrane::new(2)), "DEBCA");
+    }
+
+    #[test]
+    fn test_underflow() {
+        let moves = [
+            Move {
+                count: 1,
+                from: 0,
+                to: 1,
+            },
+            Move {
+                count: 3,
+                from: 1,
+                to: 0,
+            },
+        ];
+        assert_eq!(
+            run(&stacks(&["AB", "C"]), &moves, &CrateMover9001),
+            Err(MoveError {
+                step: 2,
+                instruction: "move 3 from 2 to 1".to_string(),
+                available: 2,
+            })
+        );
+    }
+}
@@ -0,0 +1,115 @@
+use crate::{
+    crane::{Crane, MoveError},
+    Move, Stacks,
+};
+
+/// Every state the stacks go through while running a list of moves, with a cursor to step
+/// through them. State `i` is the arrangement after the first `i` moves.
+#[derive(Clone, Debug)]
+pub struct History {
+    states: Vec<Stacks>,
+    moves: Vec<Move>,
+    cursor: usize,
+}
+
+impl History {
+    pub fn record(stacks: &Stacks, moves: &[Move], crane: &impl Crane) -> Result<Self, MoveError> {
+        let mut states = vec![stacks.clone()];
+        for (i, m) in moves.iter().enumerate() {
+            let mut next = states[i].clone();
+            next.apply(crane, m, i + 1)?;
+            states.push(next);
+        }
+        Ok(History {
+            states,
+            moves: moves.to_vec(),
+            cursor: 0,
+        })
+    }
+
+    /// Number of moves recorded.
+    pub fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    pub fn step(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn current(&self) -> &Stacks {
+        &self.states[self.cursor]
+    }
+
+    pub fn last(&self) -> &Stacks {
+        self.states.last().unwrap()
+    }
+
+    /// The move leading to the current state, `None` at the start.
+    pub fn last_move(&self) -> Option<&Move> {
+        self.cursor.checked_sub(1).map(|i| &self.moves[i])
+    }
+
+    pub fn forward(&mut self) -> Option<&Stacks> {
+        self.seek(self.cursor + 1)
+    }
+
+    pub fn back(&mut self) -> Option<&Stacks> {
+        self.seek(self.cursor.checked_sub(1)?)
+    }
+
+    pub fn seek(&mut self, step: usize) -> Option<&Stacks> {
+        (step < self.states.len()).then(|| {
+            self.cursor = step;
+            &self.states[step]
+        })
+    }
+
+    /// Each move with the arrangement it produces, in order.
+    pub fn replay(&self) -> impl Iterator<Item = (&Move, &Stacks)> {
+        self.moves.iter().zip(self.states.iter().skip(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{crane::CrateMover9000, parse_input};
+
+    static INPUT: &str = "
+    [D]
+[N] [C]
+[Z] [M] [P]
+ 1   2   3
+
+move 1 from 2 to 1
+move 3 from 1 to 3
+move 2 from 2 to 1
+move 1 from 1 to 2";
+
+    #[test]
+    fn test_step_through() {
+        let (stacks, moves) = parse_input(INPUT).unwrap();
+        let mut history = History::record(&stacks, &moves, &CrateMover9000).unwrap();
+        assert_eq!(history.len(), 4);
+        assert_eq!(history.current(), &stacks);
+        assert!(history.back().is_none());
+
+        assert_eq!(
+            history.forward().unwrap().to_string(),
+            "[D]        \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 "
+        );
+        assert_eq!(
+            history.last_move().map(|m| stacks.describe(m)),
+            Some("move 1 from 2 to 1".to_string())
+        );
+        assert_eq!(history.seek(4).unwrap().tops(), "CMZ");
+        assert!(history.forward().is_none());
+        assert_eq!(history.back().unwrap().tops(), "MZ");
+        assert_eq!(history.step(), 3);
+        assert_eq!(history.replay().count(), 4);
+    }
+}
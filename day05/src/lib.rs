@@ -0,0 +1,318 @@
+pub mod crane;
+pub mod history;
+pub mod planner;
+
+use crane::{Crane, MoveError};
+use itertools::Itertools;
+use std::fmt;
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Stacks {
+    pub labels: Vec<String>,
+    /// One stack per label, bottom crate first.
+    pub crates: Vec<Vec<char>>,
+}
+
+/// `move count from from to to`, with both stacks resolved to indices into `Stacks`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Move {
+    pub count: usize,
+    pub from: usize,
+    pub to: usize,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParseError {
+    MissingFooter,
+    DuplicateLabel(String),
+    StrayCrate { line: usize, column: usize },
+    BadInstruction { line: usize, text: String },
+    UnknownStack { line: usize, label: String },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::MissingFooter => write!(f, "drawing has no stack labels"),
+            ParseError::DuplicateLabel(label) => write!(f, "stack {} is labelled twice", label),
+            ParseError::StrayCrate { line, column } => write!(
+                f,
+                "line {}: crate at column {} is not under any stack label",
+                line, column
+            ),
+            ParseError::BadInstruction { line, text } => {
+                write!(f, "line {}: unrecognised instruction {:?}", line, text)
+            }
+            ParseError::UnknownStack { line, label } => {
+                write!(f, "line {}: there is no stack {}", line, label)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl Stacks {
+    pub fn index_of(&self, label: &str) -> Option<usize> {
+        self.labels.iter().position(|l| l == label)
+    }
+
+    pub fn tops(&self) -> String {
+        self.crates.iter().filter_map(|c| c.last()).collect()
+    }
+
+    /// Parses a drawing; `first_line` is the 1-based line number of its first row, used in errors.
+    pub fn parse(drawing: &str, first_line: usize) -> Result<Self, ParseError> {
+        let rows = drawing.lines().collect_vec();
+        let (footer, rows) = rows.split_last().ok_or(ParseError::MissingFooter)?;
+
+        let mut labels: Vec<(usize, usize, String)> = Vec::new();
+        let mut chars = footer.chars().enumerate().peekable();
+        while let Some((start, c)) = chars.next() {
+            if c.is_whitespace() {
+                continue;
+            }
+            let mut label = c.to_string();
+            while let Some((_, c)) = chars.next_if(|(_, c)| !c.is_whitespace()) {
+                label.push(c);
+            }
+            if labels.iter().any(|(_, _, l)| *l == label) {
+                return Err(ParseError::DuplicateLabel(label));
+            }
+            labels.push((start, start + label.chars().count() - 1, label));
+        }
+        if labels.is_empty() {
+            return Err(ParseError::MissingFooter);
+        }
+
+        let mut crates = vec![Vec::new(); labels.len()];
+        for (i, row) in rows.iter().enumerate().rev() {
+            let row = row.chars().collect_vec();
+            let mut col = 0;
+            while col < row.len() {
+                if row[col].is_whitespace() {
+                    col += 1;
+                    continue;
+                }
+                let err = ParseError::StrayCrate {
+                    line: first_line + i,
+                    column: col + 1,
+                };
+                if row[col] != '[' || row.get(col + 2) != Some(&']') {
+                    return Err(err);
+                }
+                let stack = labels
+                    .iter()
+                    .position(|(s, e, _)| *s <= col + 2 && col < *e + 1)
+                    .ok_or(err)?;
+                crates[stack].push(row[col + 1]);
+                col += 3;
+            }
+        }
+
+        Ok(Stacks {
+            labels: labels.into_iter().map(|(_, _, l)| l).collect(),
+            crates,
+        })
+    }
+
+    pub fn parse_move(&self, line: usize, text: &str) -> Result<Move, ParseError> {
+        let (count, from, to) = match text.split_whitespace().collect_vec()[..] {
+            ["move", count, "from", from, "to", to] => (count, from, to),
+            _ => {
+                return Err(ParseError::BadInstruction {
+                    line,
+                    text: text.to_string(),
+                })
+            }
+        };
+        let count = count.parse().map_err(|_| ParseError::BadInstruction {
+            line,
+            text: text.to_string(),
+        })?;
+        let resolve = |label: &str| {
+            self.index_of(label)
+                .ok_or_else(|| ParseError::UnknownStack {
+                    line,
+                    label: label.to_string(),
+                })
+        };
+        Ok(Move {
+            count,
+            from: resolve(from)?,
+            to: resolve(to)?,
+        })
+    }
+
+    pub fn describe(&self, m: &Move) -> String {
+        format!(
+            "move {} from {} to {}",
+            m.count, self.labels[m.from], self.labels[m.to]
+        )
+    }
+
+    pub fn apply(&mut self, crane: &impl Crane, m: &Move, step: usize) -> Result<(), MoveError> {
+        let available = self.crates[m.from].len();
+        if available < m.count {
+            return Err(MoveError {
+                step,
+                instruction: self.describe(m),
+                available,
+            });
+        }
+        if m.from != m.to {
+            crane.transfer(&mut self.crates, m.from, m.to, m.count);
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Stacks {
+    /// Renders the drawing `Stacks::parse` reads: one column per stack, at least three
+    /// characters wide, separated by a space, and the labels centered underneath.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = self
+            .labels
+            .iter()
+            .map(|l| l.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max(3);
+        let height = self.crates.iter().map(Vec::len).max().unwrap_or(0);
+        let pad = (width - 3) / 2;
+
+        for row in (0..height).rev() {
+            let line = self
+                .crates
+                .iter()
+                .map(|stack| match stack.get(row) {
+                    Some(c) => format!("{:pad$}[{}]{:rest$}", "", c, "", rest = width - 3 - pad),
+                    None => " ".repeat(width),
+                })
+                .join(" ");
+            writeln!(f, "{}", line)?;
+        }
+        let footer = self
+            .labels
+            .iter()
+            .map(|l| {
+                let left = (width - l.chars().count()) / 2;
+                format!("{:left$}{:rest$}", "", l, rest = width - left)
+            })
+            .join(" ");
+        write!(f, "{}", footer)
+    }
+}
+
+pub fn parse_input(input: &str) -> Result<(Stacks, Vec<Move>), ParseError> {
+    let lines = input.lines().collect_vec();
+    let first = lines
+        .iter()
+        .position(|l| !l.trim().is_empty())
+        .ok_or(ParseError::MissingFooter)?;
+    let blank = lines[first..]
+        .iter()
+        .position(|l| l.trim().is_empty())
+        .map_or(lines.len(), |i| first + i);
+
+    let stacks = Stacks::parse(&lines[first..blank].join("\n"), first + 1)?;
+    let moves = lines
+        .iter()
+        .enumerate()
+        .skip(blank)
+        .filter(|(_, l)| !l.trim().is_empty())
+        .map(|(i, l)| stacks.parse_move(i + 1, l))
+        .collect::<Result<_, _>>()?;
+    Ok((stacks, moves))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_many_stacks() {
+        let input = "
+[A]                                         [L]
+[B]                                     [K] [M]
+ 1   2   3   4   5   6   7   8   9  10  11  12
+
+move 2 from 1 to 10
+move 1 from 12 to 3";
+        let (stacks, moves) = parse_input(input).unwrap();
+        assert_eq!(stacks.crates.len(), 12);
+        assert_eq!(stacks.crates[0], vec!['B', 'A']);
+        assert_eq!(stacks.crates[10], vec!['K']);
+        assert_eq!(stacks.crates[11], vec!['M', 'L']);
+        assert_eq!(
+            moves,
+            vec![
+                Move {
+                    count: 2,
+                    from: 0,
+                    to: 9
+                },
+                Move {
+                    count: 1,
+                    from: 11,
+                    to: 2
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_labels() {
+        let input = "[X]     [Y]
+ a  bb   c
+
+move 1 from c to bb";
+        let (stacks, moves) = parse_input(input).unwrap();
+        assert_eq!(stacks.labels, vec!["a", "bb", "c"]);
+        assert_eq!(stacks.tops(), "XY");
+        assert_eq!(moves[0].from, 2);
+        assert_eq!(moves[0].to, 1);
+    }
+
+    #[test]
+    fn test_render() {
+        let drawing = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 ";
+        let stacks = Stacks::parse(drawing, 1).unwrap();
+        assert_eq!(stacks.to_string(), drawing);
+
+        let labels = (1..=12).map(|i| i.to_string()).collect_vec();
+        let mut crates = vec![vec![]; 12];
+        crates[9] = vec!['A', 'B'];
+        let stacks = Stacks { labels, crates };
+        assert_eq!(Stacks::parse(&stacks.to_string(), 1), Ok(stacks));
+
+        let stacks = Stacks {
+            labels: vec!["left".to_string(), "right".to_string()],
+            crates: vec![vec!['X'], vec![]],
+        };
+        assert_eq!(stacks.to_string(), " [X]       \nleft  right");
+        assert_eq!(Stacks::parse(&stacks.to_string(), 1), Ok(stacks));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            parse_input("[A]\n 1\n\nmove 1 from 1 to 2"),
+            Err(ParseError::UnknownStack {
+                line: 4,
+                label: "2".to_string()
+            })
+        );
+        assert_eq!(
+            parse_input("[A]\n 1\n\nmove one from 1 to 1"),
+            Err(ParseError::BadInstruction {
+                line: 4,
+                text: "move one from 1 to 1".to_string()
+            })
+        );
+        assert_eq!(
+            parse_input("[A]     [B]\n 1\n\n"),
+            Err(ParseError::StrayCrate { line: 1, column: 9 })
+        );
+    }
+}
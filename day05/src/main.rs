@@ -1,59 +1,50 @@
-use itertools::Itertools;
+use day05::{
+    crane::{run, Crane, CrateMover9000, CrateMover9001, LimitedCrane},
+    history::History,
+    parse_input,
+    planner::{format_plan, plan},
+    Move, Stacks,
+};
 
-fn parse_input(input: &str) -> ([Vec<char>; 9], Vec<(usize, usize, usize)>) {
-    let (crt, ins) = input.split_once("\n\n").unwrap();
-
-    let mut crates: [Vec<char>; 9] = Default::default();
-
-    crt.lines().rev().skip(1).for_each(|l| {
-        l.chars()
-            .collect::<Vec<char>>()
-            .chunks(4)
-            .enumerate()
-            .for_each(|(i, v)| {
-                if !v.iter().all(|c| c.is_whitespace()) {
-                    crates[i].push(v[1]);
-                }
-            });
-    });
+fn tops(stacks: &Stacks, instructions: &[Move], crane: &impl Crane) -> String {
+    run(stacks, instructions, crane).unwrap().tops()
+}
 
-    let instructions = ins
-        .lines()
-        .map(|l| {
-            l.split(' ')
-                .filter_map(|t| t.parse::<usize>().ok())
-                .collect_tuple()
-                .unwrap()
-        })
-        .collect();
-    (crates, instructions)
+fn print_plan(crates: &Stacks, target: &Stacks, crane: &impl Crane) {
+    let plan = plan(crates, target, crane).unwrap();
+    println!("{}", format_plan(crates, &plan.moves));
+    if !plan.shortest {
+        eprintln!("# search limit reached, there may be a shorter plan");
+    }
 }
 
-fn run(
-    crates: &[Vec<char>; 9],
-    instructions: &Vec<(usize, usize, usize)>,
-    bulk_move: bool,
-) -> String {
-    let mut tmp = crates.clone();
-    for (no, src, dest) in instructions {
-        let to_shift = (0..*no)
-            .map(|_| tmp[*src - 1].pop().unwrap())
-            .collect::<Vec<char>>();
+fn main() {
+    let (crates, instructions) = parse_input(include_str!("input")).unwrap();
+    println!("Part1: {}", tops(&crates, &instructions, &CrateMover9000));
+    println!("Part2: {}", tops(&crates, &instructions, &CrateMover9001));
 
-        if bulk_move {
-            to_shift.iter().rev().for_each(|v| tmp[*dest - 1].push(*v));
-        } else {
-            to_shift.iter().for_each(|v| tmp[*dest - 1].push(*v));
+    if std::env::args().any(|a| a == "--trace") {
+        let history = History::record(&crates, &instructions, &CrateMover9000).unwrap();
+        println!("{}", crates);
+        for (m, stacks) in history.replay() {
+            println!("\n{}\n{}", crates.describe(m), stacks);
         }
     }
 
-    tmp.iter().filter_map(|c| c.last()).collect::<String>()
-}
-
-fn main() {
-    let (crates, instructions) = parse_input(include_str!("input"));
-    println!("Part1: {}", run(&crates, &instructions, false));
-    println!("Part2: {}", run(&crates, &instructions, true));
+    let args = std::env::args().collect::<Vec<_>>();
+    // --plan <target> [9000 | 9001 | <capacity>], the CrateMover 9001 by default.
+    let mut plan_args = args.iter().skip_while(|a| *a != "--plan").skip(1);
+    if let Some(path) = plan_args.next() {
+        let target = Stacks::parse(std::fs::read_to_string(path).unwrap().trim_end(), 1).unwrap();
+        match plan_args.next().map(String::as_str) {
+            Some("9000") => print_plan(&crates, &target, &CrateMover9000),
+            None | Some("9001") => print_plan(&crates, &target, &CrateMover9001),
+            Some(capacity) => {
+                let crane = LimitedCrane::new(capacity.parse().unwrap());
+                print_plan(&crates, &target, &crane)
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -73,13 +64,13 @@ move 1 from 1 to 2";
 
     #[test]
     fn test_part1() {
-        let (crates, instructions) = parse_input(INPUT);
-        assert_eq!(run(&crates, &instructions, false), "CMZ");
+        let (crates, instructions) = parse_input(INPUT).unwrap();
+        assert_eq!(tops(&crates, &instructions, &CrateMover9000), "CMZ");
     }
 
     #[test]
     fn test_part2() {
-        let (crates, instructions) = parse_input(INPUT);
-        assert_eq!(run(&crates, &instructions, true), "MCD");
+        let (crates, instructions) = parse_input(INPUT).unwrap();
+        assert_eq!(tops(&crates, &instructions, &CrateMover9001), "MCD");
     }
 }
@@ -0,0 +1,259 @@
+use crate::{crane::Crane, Move, Stacks};
+use itertools::Itertools;
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+};
+
+/// Arrangements explored by the exact search before falling back to building the target
+/// one crate at a time.
+const SEARCH_LIMIT: usize = 50_000;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PlanError {
+    LabelsDiffer,
+    CratesDiffer,
+    Unreachable,
+    TooLarge,
+}
+
+/// Moves turning one arrangement into another.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Plan {
+    pub moves: Vec<Move>,
+    /// Whether no plan has fewer moves; `false` when the search was given up and the target
+    /// built one crate at a time instead.
+    pub shortest: bool,
+}
+
+enum Search {
+    Found(Vec<Move>),
+    Exhausted,
+    LimitReached,
+}
+
+impl fmt::Display for PlanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PlanError::LabelsDiffer => write!(f, "start and target have different stacks"),
+            PlanError::CratesDiffer => write!(f, "start and target hold different crates"),
+            PlanError::Unreachable => write!(f, "no sequence of moves reaches the target"),
+            PlanError::TooLarge => write!(f, "too many arrangements to search with two stacks"),
+        }
+    }
+}
+
+impl std::error::Error for PlanError {}
+
+type Crates = Vec<Vec<char>>;
+
+/// Finds moves turning `start` into `target` with the given crane.
+///
+/// Small arrangements are solved exactly with a breadth-first search over every
+/// `move N from A to B`; larger ones are built bottom-up one crate at a time, which every
+/// crane can do, so the result is valid but not necessarily the shortest, as reported in
+/// [`Plan::shortest`].
+pub fn plan(start: &Stacks, target: &Stacks, crane: &impl Crane) -> Result<Plan, PlanError> {
+    if start.labels != target.labels {
+        return Err(PlanError::LabelsDiffer);
+    }
+    let sorted = |s: &Stacks| s.crates.iter().flatten().copied().sorted().collect_vec();
+    if sorted(start) != sorted(target) {
+        return Err(PlanError::CratesDiffer);
+    }
+
+    match search(&start.crates, &target.crates, crane) {
+        Search::Found(moves) => Ok(Plan {
+            moves,
+            shortest: true,
+        }),
+        Search::Exhausted => Err(PlanError::Unreachable),
+        Search::LimitReached if start.crates.len() >= 3 => Ok(Plan {
+            moves: compress(build(&start.crates, &target.crates), crane),
+            shortest: false,
+        }),
+        Search::LimitReached => Err(PlanError::TooLarge),
+    }
+}
+
+pub fn format_plan(stacks: &Stacks, moves: &[Move]) -> String {
+    moves.iter().map(|m| stacks.describe(m)).join("\n")
+}
+
+/// Breadth-first search for the shortest plan, giving up after `SEARCH_LIMIT` arrangements.
+fn search(start: &Crates, target: &Crates, crane: &impl Crane) -> Search {
+    let mut seen: HashMap<Crates, Option<(Crates, Move)>> = HashMap::from([(start.clone(), None)]);
+    let mut queue = VecDeque::from([start.clone()]);
+
+    while let Some(state) = queue.pop_front() {
+        if state == *target {
+            let mut moves = Vec::new();
+            let mut cur = &state;
+            while let Some((prev, m)) = &seen[cur] {
+                moves.push(*m);
+                cur = prev;
+            }
+            moves.reverse();
+            return Search::Found(moves);
+        }
+
+        for (from, to) in (0..state.len()).cartesian_product(0..state.len()) {
+            if from == to {
+                continue;
+            }
+            for count in 1..=state[from].len() {
+                let mut next = state.clone();
+                crane.transfer(&mut next, from, to, count);
+                if seen.contains_key(&next) {
+                    continue;
+                }
+                if seen.len() >= SEARCH_LIMIT {
+                    return Search::LimitReached;
+                }
+                seen.insert(
+                    next.clone(),
+                    Some((state.clone(), Move { count, from, to })),
+                );
+                queue.push_back(next);
+            }
+        }
+    }
+    Search::Exhausted
+}
+
+/// Places the target crates bottom-up, stack by stack, with single-crate moves. Crates in
+/// the way go to unfinished stacks, or are parked on top of finished ones until needed.
+fn build(start: &Crates, target: &Crates) -> Vec<Move> {
+    let mut stacks = start.clone();
+    let mut moves = Vec::new();
+    let mut shift = |stacks: &mut Crates, from: usize, to: usize| {
+        let c = stacks[from].pop().unwrap();
+        stacks[to].push(c);
+        moves.push(Move { count: 1, from, to });
+    };
+
+    for s in 0..target.len() {
+        // Crates at or below this height on stack `i` are in their final place.
+        let floor = |i: usize| if i < s { target[i].len() } else { 0 };
+        let buffer = |stacks: &Crates, avoid: &[usize]| {
+            (s + 1..stacks.len())
+                .filter(|i| !avoid.contains(i))
+                .min_by_key(|i| stacks[*i].len())
+                .or_else(|| (0..s).find(|i| !avoid.contains(i)))
+                .unwrap()
+        };
+
+        for (p, &c) in target[s].iter().enumerate() {
+            if stacks[s].get(p) == Some(&c) {
+                continue;
+            }
+            while stacks[s].len() > p {
+                let to = buffer(&stacks, &[s]);
+                shift(&mut stacks, s, to);
+            }
+
+            let (u, depth) = (0..stacks.len())
+                .filter(|u| *u != s)
+                .flat_map(|u| {
+                    let stack = &stacks[u];
+                    (floor(u)..stack.len())
+                        .filter(|i| stack[*i] == c)
+                        .map(move |i| (u, stack.len() - 1 - i))
+                })
+                .min_by_key(|(_, depth)| *depth)
+                .unwrap();
+            for _ in 0..depth {
+                let to = buffer(&stacks, &[s, u]);
+                shift(&mut stacks, u, to);
+            }
+            shift(&mut stacks, u, s);
+        }
+        while stacks[s].len() > target[s].len() {
+            let to = buffer(&stacks, &[s]);
+            shift(&mut stacks, s, to);
+        }
+    }
+    moves
+}
+
+/// Joins runs of single-crate moves between the same stacks when the crane lifts one crate at
+/// a time, since that is exactly what it does for `move N`.
+fn compress(moves: Vec<Move>, crane: &impl Crane) -> Vec<Move> {
+    if crane.capacity() != 1 {
+        return moves;
+    }
+    moves
+        .into_iter()
+        .coalesce(|a, b| match (a.from, a.to) == (b.from, b.to) {
+            true => Ok(Move {
+                count: a.count + b.count,
+                ..a
+            }),
+            false => Err((a, b)),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crane::{run, CrateMover9000, CrateMover9001, LimitedCrane};
+
+    fn drawing(s: &str) -> Stacks {
+        Stacks::parse(s, 1).unwrap()
+    }
+
+    /// Checks the plan reaches the target, giving its length and whether it is the shortest.
+    fn check(start: &Stacks, target: &Stacks, crane: &impl Crane) -> (usize, bool) {
+        let plan = plan(start, target, crane).unwrap();
+        assert_eq!(&run(start, &plan.moves, crane).unwrap(), target);
+        (plan.moves.len(), plan.shortest)
+    }
+
+    #[test]
+    fn test_sample() {
+        let start = drawing("    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 ");
+        let target = drawing("        [Z]\n        [N]\n    [M] [D]\n[C]     [P]\n 1   2   3 ");
+        assert_eq!(check(&start, &target, &CrateMover9000), (3, true));
+        let (len, shortest) = check(&start, &target, &CrateMover9001);
+        assert!(len <= 4 && shortest);
+
+        let target = drawing("        [D]\n        [C]\n[M] [N] [P]\n[Z]     [N]\n 1   2   3 ");
+        assert_eq!(
+            plan(&start, &target, &CrateMover9000),
+            Err(PlanError::CratesDiffer)
+        );
+    }
+
+    #[test]
+    fn test_large() {
+        let start = drawing(
+            "[A] [E] [I] [M]
+[B] [F] [J] [N]
+[C] [G] [K] [O]
+[D] [H] [L] [P]
+ 1   2   3   4 ",
+        );
+        let target = drawing(
+            "[P] [L] [H] [D]
+[O] [K] [G] [C]
+[N] [J] [F] [B]
+[M] [I] [E] [A]
+ 1   2   3   4 ",
+        );
+        assert!(!check(&start, &target, &CrateMover9000).1);
+        assert!(!check(&start, &target, &CrateMover9001).1);
+        assert!(!check(&start, &target, &LimitedCrane::new(3)).1);
+    }
+
+    #[test]
+    fn test_two_stacks() {
+        let start = drawing("[A]    \n[B]    \n 1   2 ");
+        let target = drawing("    [A]\n    [B]\n 1   2 ");
+        assert_eq!(check(&start, &target, &CrateMover9001), (1, true));
+        assert_eq!(
+            plan(&start, &target, &CrateMover9000),
+            Err(PlanError::Unreachable)
+        );
+    }
+}
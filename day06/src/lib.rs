@@ -0,0 +1,412 @@
+use std::io::{self, BufReader, Bytes, Read};
+
+pub const PACKET_MARKER: usize = 4;
+pub const MESSAGE_MARKER: usize = 14;
+
+/// Tracks the last `window` bytes of a stream with a count per byte value, so checking
+/// whether they are all different costs O(1) per byte whatever the window size.
+#[derive(Clone, Debug)]
+pub struct MarkerDetector {
+    window: usize,
+    recent: Vec<u8>,
+    counts: [usize; 256],
+    duplicates: usize,
+    seen: usize,
+}
+
+impl MarkerDetector {
+    pub fn new(window: usize) -> Self {
+        MarkerDetector {
+            window,
+            recent: vec![0; window],
+            counts: [0; 256],
+            duplicates: 0,
+            seen: 0,
+        }
+    }
+
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// Bytes pushed since creation or the last `reset`.
+    pub fn seen(&self) -> usize {
+        self.seen
+    }
+
+    /// Adds a byte and tells whether the last `window` bytes are now all different.
+    pub fn push(&mut self, byte: u8) -> bool {
+        if self.window == 0 {
+            self.seen += 1;
+            return true;
+        }
+        let slot = self.seen % self.window;
+        if self.seen >= self.window {
+            let old = self.recent[slot] as usize;
+            self.counts[old] -= 1;
+            if self.counts[old] == 1 {
+                self.duplicates -= 1;
+            }
+        }
+        self.recent[slot] = byte;
+        self.counts[byte as usize] += 1;
+        if self.counts[byte as usize] == 2 {
+            self.duplicates += 1;
+        }
+        self.seen += 1;
+        self.seen >= self.window && self.duplicates == 0
+    }
+
+    pub fn reset(&mut self) {
+        self.counts = [0; 256];
+        self.duplicates = 0;
+        self.seen = 0;
+    }
+}
+
+/// Number of bytes read up to and including the first marker of `window` distinct bytes,
+/// or `None` if the stream ends first.
+pub fn find_marker<R: Read>(reader: R, window: usize) -> io::Result<Option<usize>> {
+    let mut detector = MarkerDetector::new(window);
+    if window == 0 {
+        return Ok(Some(0));
+    }
+    for byte in BufReader::new(reader).bytes() {
+        if detector.push(byte?) {
+            return Ok(Some(detector.seen()));
+        }
+    }
+    Ok(None)
+}
+
+pub fn find_marker_str(input: &str, window: usize) -> Option<usize> {
+    find_marker(input.as_bytes(), window).unwrap()
+}
+
+/// Offsets just past every marker in the stream. A marker's window never overlaps the
+/// previous marker.
+pub struct Markers<R> {
+    bytes: Bytes<BufReader<R>>,
+    detector: MarkerDetector,
+    pos: usize,
+}
+
+pub fn markers<R: Read>(reader: R, window: usize) -> Markers<R> {
+    assert!(window > 0, "markers need a window of at least one byte");
+    Markers {
+        bytes: BufReader::new(reader).bytes(),
+        detector: MarkerDetector::new(window),
+        pos: 0,
+    }
+}
+
+impl<R: Read> Iterator for Markers<R> {
+    type Item = io::Result<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for byte in self.bytes.by_ref() {
+            let byte = match byte {
+                Ok(byte) => byte,
+                Err(e) => return Some(Err(e)),
+            };
+            self.pos += 1;
+            if self.detector.push(byte) {
+                self.detector.reset();
+                return Some(Ok(self.pos));
+            }
+        }
+        None
+    }
+}
+
+/// Cuts a stream at every marker of one size, fed a byte at a time.
+#[derive(Clone, Debug)]
+struct Framer {
+    detector: MarkerDetector,
+    /// Offset just past the last marker, once there has been one.
+    start: Option<usize>,
+    payload: Vec<u8>,
+}
+
+impl Framer {
+    fn new(window: usize) -> Self {
+        Framer {
+            detector: MarkerDetector::new(window),
+            start: None,
+            payload: Vec::new(),
+        }
+    }
+
+    /// Adds the byte ending at offset `pos`, returning the frame it closes, if any.
+    fn push(&mut self, byte: u8, pos: usize) -> Option<(usize, Vec<u8>)> {
+        self.payload.push(byte);
+        if !self.detector.push(byte) {
+            return None;
+        }
+        self.detector.reset();
+        self.payload
+            .truncate(self.payload.len() - self.detector.window());
+        let payload = std::mem::take(&mut self.payload);
+        self.start.replace(pos).map(|start| (start, payload))
+    }
+
+    /// The frame left open at the end of the stream.
+    fn finish(&mut self) -> Option<(usize, Vec<u8>)> {
+        self.start
+            .take()
+            .map(|start| (start, std::mem::take(&mut self.payload)))
+    }
+}
+
+/// The stream cut at every marker: each record is the offset just past a marker and the bytes
+/// from there up to where the next marker starts, or to the end of the stream. Bytes before
+/// the first marker are skipped.
+pub struct Frames<R> {
+    bytes: Bytes<BufReader<R>>,
+    framer: Framer,
+    pos: usize,
+}
+
+pub fn frames<R: Read>(reader: R, window: usize) -> Frames<R> {
+    assert!(window > 0, "frames need a window of at least one byte");
+    Frames {
+        bytes: BufReader::new(reader).bytes(),
+        framer: Framer::new(window),
+        pos: 0,
+    }
+}
+
+impl<R: Read> Iterator for Frames<R> {
+    type Item = io::Result<(usize, Vec<u8>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for byte in self.bytes.by_ref() {
+            let byte = match byte {
+                Ok(byte) => byte,
+                Err(e) => return Some(Err(e)),
+            };
+            self.pos += 1;
+            if let Some(frame) = self.framer.push(byte, self.pos) {
+                return Some(Ok(frame));
+            }
+        }
+        self.framer.finish().map(Ok)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Marker {
+    Packet,
+    Message,
+}
+
+impl Marker {
+    pub const ALL: [Marker; 2] = [Marker::Packet, Marker::Message];
+
+    pub fn window(&self) -> usize {
+        match self {
+            Marker::Packet => PACKET_MARKER,
+            Marker::Message => MESSAGE_MARKER,
+        }
+    }
+}
+
+/// A frame of `frames`, for one kind of marker.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Record {
+    pub marker: Marker,
+    pub offset: usize,
+    pub payload: Vec<u8>,
+}
+
+/// Both kinds of marker found in one pass, each cut as `frames` does independently of the
+/// other, and given back in order of offset: the packets inside a message come right after
+/// it, once the message has ended.
+pub struct Records<R> {
+    bytes: Bytes<BufReader<R>>,
+    framers: [Framer; Marker::ALL.len()],
+    pos: usize,
+    /// Frames closed but not yet given back, in case an earlier one is still open.
+    closed: Vec<Record>,
+}
+
+pub fn records<R: Read>(reader: R) -> Records<R> {
+    Records {
+        bytes: BufReader::new(reader).bytes(),
+        framers: Marker::ALL.map(|m| Framer::new(m.window())),
+        pos: 0,
+        closed: Vec::new(),
+    }
+}
+
+impl<R> Records<R> {
+    /// The closed record with the lowest offset, if no open frame starts before it.
+    fn ready(&mut self, ended: bool) -> Option<Record> {
+        let (i, first) = self
+            .closed
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, r)| r.offset)?;
+        let open = self.framers.iter().filter_map(|f| f.start).min();
+        match ended || open.is_none_or(|start| first.offset < start) {
+            true => Some(self.closed.swap_remove(i)),
+            false => None,
+        }
+    }
+}
+
+impl<R: Read> Iterator for Records<R> {
+    type Item = io::Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(byte) = self.bytes.next() {
+            let byte = match byte {
+                Ok(byte) => byte,
+                Err(e) => return Some(Err(e)),
+            };
+            self.pos += 1;
+            for (marker, framer) in Marker::ALL.iter().zip(&mut self.framers) {
+                if let Some((offset, payload)) = framer.push(byte, self.pos) {
+                    self.closed.push(Record {
+                        marker: *marker,
+                        offset,
+                        payload,
+                    });
+                }
+            }
+            if let Some(record) = self.ready(false) {
+                return Some(Ok(record));
+            }
+        }
+        for (marker, framer) in Marker::ALL.iter().zip(&mut self.framers) {
+            if let Some((offset, payload)) = framer.finish() {
+                self.closed.push(Record {
+                    marker: *marker,
+                    offset,
+                    payload,
+                });
+            }
+        }
+        self.ready(true).map(Ok)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_marker() {
+        assert_eq!(find_marker_str("abcabcabc", 4), None);
+        assert_eq!(find_marker_str("", 1), None);
+        assert_eq!(find_marker_str("aab", 2), Some(3));
+        assert_eq!(find_marker_str("abc", 0), Some(0));
+    }
+
+    #[test]
+    fn test_markers() {
+        let stream = "aabcdxxxwxyzabcd";
+        assert_eq!(
+            markers(stream.as_bytes(), PACKET_MARKER)
+                .collect::<io::Result<Vec<_>>>()
+                .unwrap(),
+            vec![5, 12, 16]
+        );
+        assert_eq!(markers(stream.as_bytes(), 8).count(), 1);
+        assert_eq!(markers(stream.as_bytes(), 9).count(), 0);
+    }
+
+    #[test]
+    fn test_frames() {
+        let stream = "aaaabcdeeeeeeeffghijjjj";
+        let frames = frames(stream.as_bytes(), PACKET_MARKER)
+            .map(|f| f.map(|(o, p)| (o, String::from_utf8(p).unwrap())))
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            frames,
+            vec![(7, "eeeeeeef".to_string()), (19, "jjjj".to_string())]
+        );
+
+        let stream = "aa".to_string() + "abcdefghijklmn" + "payload" + "nmlkjihgfedcba" + "tail";
+        let frames = super::frames(stream.as_bytes(), MESSAGE_MARKER)
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            frames,
+            vec![(16, b"payload".to_vec()), (37, b"tail".to_vec())]
+        );
+    }
+
+    struct Broken;
+
+    impl Read for Broken {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::other("unplugged"))
+        }
+    }
+
+    #[test]
+    fn test_reader() {
+        let long = "ab".repeat(100_000) + "cd";
+        assert_eq!(find_marker(long.as_bytes(), 3).unwrap(), Some(200_001));
+        assert_eq!(find_marker(long.as_bytes(), 4).unwrap(), Some(200_002));
+        assert_eq!(find_marker(long.as_bytes(), 5).unwrap(), None);
+        assert!(find_marker(Broken, 4).is_err());
+        assert!(frames(Broken, 4).next().unwrap().is_err());
+        assert!(records(Broken).next().unwrap().is_err());
+    }
+
+    fn tags(stream: &str) -> Vec<(Marker, usize, String)> {
+        records(stream.as_bytes())
+            .map(|r| {
+                let r = r.unwrap();
+                (r.marker, r.offset, String::from_utf8(r.payload).unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_records() {
+        use Marker::*;
+        let sample = "mjqjpqmgbljsphdztnvjfqwrcgsmlb";
+        assert_eq!(
+            tags(sample),
+            vec![
+                (Packet, 7, String::new()),
+                (Packet, 11, String::new()),
+                (Packet, 15, String::new()),
+                (Packet, 19, String::new()),
+                (Message, 19, "jfqwrcgsmlb".to_string()),
+                (Packet, 23, String::new()),
+                (Packet, 27, "mlb".to_string()),
+            ]
+        );
+        for sample in [
+            "bvwbjplbgvbhsrlpgdmjqwftvncz",
+            "nppdvjthqldpwncqszvftbrmjlhg",
+            "nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg",
+            "zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw",
+        ] {
+            let records = records(sample.as_bytes())
+                .collect::<io::Result<Vec<_>>>()
+                .unwrap();
+            assert!(records.windows(2).all(|w| w[0].offset <= w[1].offset));
+            for marker in Marker::ALL {
+                let ours = records
+                    .iter()
+                    .filter(|r| r.marker == marker)
+                    .map(|r| (r.offset, r.payload.clone()))
+                    .collect::<Vec<_>>();
+                let alone = frames(sample.as_bytes(), marker.window())
+                    .collect::<io::Result<Vec<_>>>()
+                    .unwrap();
+                assert_eq!(ours, alone);
+                assert_eq!(
+                    ours.first().map(|r| r.0),
+                    find_marker_str(sample, marker.window())
+                );
+            }
+        }
+    }
+}
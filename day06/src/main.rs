@@ -1,24 +1,25 @@
-use std::collections::HashSet;
+use day06::{find_marker_str, records, MESSAGE_MARKER, PACKET_MARKER};
 
 fn run(input: &str, window_size: usize) -> usize {
-    input
-        .chars()
-        .collect::<Vec<char>>()
-        .windows(window_size)
-        .enumerate()
-        .find(|(_i, v)| {
-            let h: HashSet<&char> = HashSet::from_iter(v.iter());
-            h.len() == window_size
-        })
-        .unwrap()
-        .0
-        + window_size
+    find_marker_str(input, window_size).expect("no marker in the datastream")
 }
 
 fn main() {
     let input = include_str!("input").trim();
-    println!("Part1: {}", run(input, 4));
-    println!("Part2: {}", run(input, 14));
+    println!("Part1: {}", run(input, PACKET_MARKER));
+    println!("Part2: {}", run(input, MESSAGE_MARKER));
+
+    if std::env::args().any(|a| a == "--frames") {
+        for record in records(input.as_bytes()) {
+            let record = record.unwrap();
+            println!(
+                "{:?} {}: {}",
+                record.marker,
+                record.offset,
+                String::from_utf8_lossy(&record.payload)
+            );
+        }
+    }
 }
 
 #[cfg(test)]
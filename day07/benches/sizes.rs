@@ -0,0 +1,84 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use day07::{shell::exec_commands, FileSystem, Kind};
+
+/// A transcript exploring `dirs` directories, each holding one file. Directory `i` is a
+/// child of `(i - 1) / fanout`, so a fanout of 1 gives a single chain as deep as it is long.
+fn transcript(dirs: usize, fanout: usize) -> String {
+    let children = |id: usize| (id * fanout + 1..=id * fanout + fanout).filter(|c| *c < dirs);
+    let mut out = String::from("$ cd /\n");
+    let mut stack = vec![children(0)];
+    out += &format!("$ ls\n{} f0\n", 1000);
+    children(0).for_each(|c| out += &format!("dir d{}\n", c));
+
+    while let Some(pending) = stack.last_mut() {
+        match pending.next() {
+            Some(child) => {
+                out += &format!(
+                    "$ cd d{}\n$ ls\n{} f{}\n",
+                    child,
+                    1000 + child % 7919,
+                    child
+                );
+                children(child).for_each(|c| out += &format!("dir d{}\n", c));
+                stack.push(children(child));
+            }
+            None => {
+                stack.pop();
+                if !stack.is_empty() {
+                    out += "$ cd ..\n";
+                }
+            }
+        }
+    }
+    out
+}
+
+/// What `part1` and `part2` used to do: walk every directory's subtree.
+fn walk_sizes(fs: &FileSystem) -> usize {
+    fs.dirs()
+        .map(|d| {
+            d.descendants(fs.arena())
+                .map(|n| match fs.arena()[n].get().kind {
+                    Kind::File(size) => size,
+                    Kind::Dir => 0,
+                })
+                .sum::<usize>()
+        })
+        .filter(|s| *s <= 100000)
+        .sum()
+}
+
+fn cached_sizes(fs: &mut FileSystem) -> usize {
+    fs.compute_sizes();
+    fs.dirs().map(|d| fs.size(d)).filter(|s| *s <= 100000).sum()
+}
+
+fn bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sizes");
+    group.sample_size(10);
+    for (shape, fanout) in [("chain", 1), ("wide", 8)] {
+        for dirs in [10_000, 100_000] {
+            let (fs, diagnostics) = exec_commands(&transcript(dirs, fanout));
+            assert!(diagnostics.is_empty());
+            assert_eq!(fs.dirs().count(), dirs);
+            let id = format!("{}/{}", shape, dirs);
+
+            if dirs <= 10_000 || fanout > 1 {
+                group.bench_with_input(BenchmarkId::new("walk", &id), &fs, |b, fs| {
+                    b.iter(|| walk_sizes(black_box(fs)))
+                });
+            }
+            group.bench_with_input(BenchmarkId::new("post-order", &id), &fs, |b, fs| {
+                b.iter_batched_ref(
+                    || fs.clone(),
+                    |fs| cached_sizes(black_box(fs)),
+                    criterion::BatchSize::LargeInput,
+                )
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
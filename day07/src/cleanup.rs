@@ -0,0 +1,318 @@
+use crate::FileSystem;
+use indextree::{NodeEdge, NodeId};
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Disk {
+    pub capacity: usize,
+    pub required: usize,
+}
+
+impl Default for Disk {
+    fn default() -> Self {
+        Disk {
+            capacity: 70_000_000,
+            required: 30_000_000,
+        }
+    }
+}
+
+impl Disk {
+    /// How much has to be deleted from `fs` to get `required` free space.
+    pub fn needed(&self, fs: &FileSystem) -> usize {
+        let free = self.capacity.saturating_sub(fs.size(fs.root()));
+        self.required.saturating_sub(free)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Candidates {
+    Dirs,
+    Files,
+    Both,
+}
+
+impl FromStr for Candidates {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dirs" => Ok(Candidates::Dirs),
+            "files" => Ok(Candidates::Files),
+            "both" => Ok(Candidates::Both),
+            _ => Err(format!(
+                "invalid candidates {:?}, expected dirs, files or both",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Plan {
+    pub needed: usize,
+    pub freed: usize,
+    /// Paths and sizes, none of them inside another.
+    pub remove: Vec<(String, usize)>,
+}
+
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (path, size) in &self.remove {
+            writeln!(f, "rm -r {} # {}", path, size)?;
+        }
+        write!(f, "# frees {} of the {} needed", self.freed, self.needed)
+    }
+}
+
+/// Totals below `needed`, one bit each.
+struct Bits(Vec<u64>);
+
+impl Bits {
+    fn new(len: usize) -> Self {
+        Bits(vec![0; len.div_ceil(64)])
+    }
+
+    fn set(&mut self, i: usize) {
+        self.0[i / 64] |= 1 << (i % 64);
+    }
+
+    /// Every total `by` higher, dropping those reaching `len`.
+    fn shifted(&self, by: usize, len: usize) -> Bits {
+        let mut out = Bits::new(len);
+        let (words, offset) = (by / 64, by % 64);
+        for (i, w) in self.0.iter().enumerate().filter(|(_, w)| **w != 0) {
+            if let Some(o) = out.0.get_mut(i + words) {
+                *o |= w << offset;
+            }
+            if let (true, Some(o)) = (offset > 0, out.0.get_mut(i + words + 1)) {
+                *o |= w >> (64 - offset);
+            }
+        }
+        if let (true, Some(last)) = (!len.is_multiple_of(64), out.0.last_mut()) {
+            *last &= (1 << (len % 64)) - 1;
+        }
+        out
+    }
+
+    /// The smallest total from `from` on.
+    fn first_from(&self, from: usize) -> Option<usize> {
+        let start = from / 64;
+        let mask = u64::MAX.checked_shl((from % 64) as u32).unwrap_or(0);
+        self.0
+            .get(start..)?
+            .iter()
+            .enumerate()
+            .map(|(i, w)| (start + i, if i == 0 { w & mask } else { *w }))
+            .find(|(_, w)| *w != 0)
+            .map(|(i, w)| i * 64 + w.trailing_zeros() as usize)
+    }
+}
+
+/// Picks the entries to delete so that at least the needed space is freed, with the smallest
+/// total size among sets of entries where none is inside another.
+///
+/// Goes through the entries in pre-order, each of them either left, moving on to the next,
+/// or deleted whole, moving on past everything inside it. Every freed total below `needed`
+/// is tracked, with the entry whose deletion first reached it, and of the others only the
+/// smallest. `None` when even deleting everything is not enough. The root directory itself
+/// is never a candidate.
+pub fn plan_deletion(fs: &FileSystem, disk: &Disk, candidates: Candidates) -> Option<Plan> {
+    let needed = disk.needed(fs);
+    let eligible = |id: NodeId| match (candidates, fs.is_dir(id)) {
+        _ if id == fs.root() => false,
+        (Candidates::Both, _) => true,
+        (Candidates::Dirs, is_dir) => is_dir,
+        (Candidates::Files, is_dir) => !is_dir,
+    };
+
+    // Entries in pre-order, with the position just past everything inside each.
+    let mut order = Vec::new();
+    let mut end = Vec::new();
+    let mut open = Vec::new();
+    for edge in fs.root().traverse(fs.arena()) {
+        match edge {
+            NodeEdge::Start(id) => {
+                open.push(order.len());
+                order.push(id);
+                end.push(0);
+            }
+            NodeEdge::End(_) => end[open.pop().unwrap()] = order.len(),
+        }
+    }
+
+    // Totals reachable before the current position, and deletions landing further on.
+    let mut reached = Bits::new(needed);
+    let mut first = vec![u32::MAX; needed];
+    let mut pending: Vec<(usize, usize, Bits)> = Vec::new();
+    // The smallest total reaching `needed`: total, last entry deleted, total before it.
+    let mut best = match needed {
+        0 => Some((0, None, 0)),
+        _ => {
+            reached.set(0);
+            None
+        }
+    };
+    for (i, id) in order.iter().enumerate() {
+        for (_, entry, bits) in pending.extract_if(.., |(end, _, _)| *end == i) {
+            for (w, (r, b)) in reached.0.iter_mut().zip(bits.0).enumerate() {
+                let mut new = b & !*r;
+                *r |= new;
+                while new != 0 {
+                    first[w * 64 + new.trailing_zeros() as usize] = entry as u32;
+                    new &= new - 1;
+                }
+            }
+        }
+        if !eligible(*id) {
+            continue;
+        }
+        let size = fs.size(*id);
+        if let Some(t) = reached.first_from(needed.saturating_sub(size)) {
+            if best.is_none_or(|(b, _, _)| t + size < b) {
+                best = Some((t + size, Some(i), t));
+            }
+        }
+        if size < needed {
+            pending.push((end[i], i, reached.shifted(size, needed)));
+        }
+    }
+
+    let (freed, last, mut total) = best?;
+    let mut remove = last.into_iter().map(|i| order[i]).collect::<Vec<_>>();
+    while total > 0 {
+        let id = order[first[total] as usize];
+        remove.push(id);
+        total -= fs.size(id);
+    }
+    let mut remove = remove
+        .into_iter()
+        .map(|id| (fs.path(id), fs.size(id)))
+        .collect::<Vec<_>>();
+    remove.sort();
+
+    Some(Plan {
+        needed,
+        freed,
+        remove,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::sample;
+
+    #[test]
+    fn test_candidates() {
+        assert_eq!("files".parse(), Ok(Candidates::Files));
+        assert_eq!(
+            "all".parse::<Candidates>(),
+            Err("invalid candidates \"all\", expected dirs, files or both".to_string())
+        );
+    }
+
+    #[test]
+    fn test_puzzle_budget() {
+        let fs = sample();
+        let plan = plan_deletion(&fs, &Disk::default(), Candidates::Dirs).unwrap();
+        assert_eq!(plan.needed, 8381165);
+        assert_eq!(plan.freed, 24933642);
+        assert_eq!(plan.remove, vec![("/d".to_string(), 24933642)]);
+    }
+
+    /// Smallest total over every set of non-nested candidates freeing enough.
+    fn brute_force(fs: &FileSystem, disk: &Disk, candidates: Candidates) -> Option<usize> {
+        let nodes = fs
+            .root()
+            .descendants(fs.arena())
+            .skip(1)
+            .filter(|n| match candidates {
+                Candidates::Dirs => fs.is_dir(*n),
+                Candidates::Files => !fs.is_dir(*n),
+                Candidates::Both => true,
+            })
+            .collect::<Vec<_>>();
+        (0u32..1 << nodes.len())
+            .map(|mask| {
+                (0..nodes.len())
+                    .filter(|i| mask & 1 << i != 0)
+                    .map(|i| nodes[i])
+                    .collect::<Vec<_>>()
+            })
+            .filter(|set| {
+                set.iter().all(|a| {
+                    set.iter()
+                        .all(|b| a == b || !b.ancestors(fs.arena()).any(|x| x == *a))
+                })
+            })
+            .map(|set| set.iter().map(|n| fs.size(*n)).sum::<usize>())
+            .filter(|total| *total >= disk.needed(fs))
+            .min()
+    }
+
+    #[test]
+    fn test_several_entries() {
+        let fs = sample();
+        for required in [30_000_000, 40_000_000, 50_000_000, 60_000_000] {
+            let disk = Disk {
+                capacity: 70_000_000,
+                required,
+            };
+            for candidates in [Candidates::Dirs, Candidates::Files, Candidates::Both] {
+                let plan = plan_deletion(&fs, &disk, candidates);
+                assert_eq!(
+                    plan.as_ref().map(|p| p.freed),
+                    brute_force(&fs, &disk, candidates)
+                );
+                if let Some(plan) = plan {
+                    assert_eq!(
+                        plan.remove.iter().map(|(_, s)| s).sum::<usize>(),
+                        plan.freed
+                    );
+                }
+            }
+        }
+
+        let disk = Disk {
+            capacity: 70_000_000,
+            required: 50_000_000,
+        };
+        let plan = plan_deletion(&fs, &disk, Candidates::Files).unwrap();
+        assert_eq!(
+            plan.remove,
+            vec![
+                ("/b.txt".to_string(), 14848514),
+                ("/d/d.ext".to_string(), 5626152),
+                ("/d/d.log".to_string(), 8033020),
+            ]
+        );
+
+        let disk = Disk {
+            capacity: 40_000_000,
+            required: 40_000_000,
+        };
+        assert_eq!(plan_deletion(&fs, &disk, Candidates::Dirs), None);
+    }
+
+    #[test]
+    fn test_many_totals() {
+        // 2048 distinct totals below 1_000_000, the best plan needing exactly 1234 of them.
+        let mut fs = FileSystem::new();
+        let root = fs.root();
+        let small = fs.mkdir(root, "small");
+        for k in 0..11 {
+            fs.touch(small, &format!("f{}", k), 1 << k);
+        }
+        fs.touch(root, "big", 1_000_000 - 1234);
+        fs.compute_sizes();
+        let disk = Disk {
+            capacity: fs.size(root),
+            required: 1_000_000,
+        };
+        let plan = plan_deletion(&fs, &disk, Candidates::Files).unwrap();
+        assert_eq!(plan.freed, 1_000_000);
+        let sizes = plan.remove.iter().map(|(_, s)| *s).collect::<Vec<_>>();
+        assert_eq!(sizes, vec![998_766, 2, 1024, 16, 64, 128]);
+    }
+}
@@ -0,0 +1,322 @@
+pub mod cleanup;
+pub mod shell;
+
+use indextree::{Arena, NodeEdge, NodeId};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Kind {
+    Dir,
+    File(usize),
+}
+
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub name: String,
+    pub kind: Kind,
+    /// Size of the whole subtree, filled in by `FileSystem::compute_sizes`.
+    total: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct FileSystem {
+    arena: Arena<Node>,
+    root: NodeId,
+    /// Whether every node's `total` is up to date.
+    sized: bool,
+}
+
+impl Default for FileSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FileSystem {
+    pub fn new() -> Self {
+        let mut arena = Arena::new();
+        let root = arena.new_node(Node {
+            name: "/".to_string(),
+            kind: Kind::Dir,
+            total: 0,
+        });
+        FileSystem {
+            arena,
+            root,
+            sized: true,
+        }
+    }
+
+    pub fn root(&self) -> NodeId {
+        self.root
+    }
+
+    pub fn arena(&self) -> &Arena<Node> {
+        &self.arena
+    }
+
+    pub fn node(&self, id: NodeId) -> &Node {
+        self.arena[id].get()
+    }
+
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.arena[id].parent()
+    }
+
+    pub fn is_dir(&self, id: NodeId) -> bool {
+        self.node(id).kind == Kind::Dir
+    }
+
+    pub fn child(&self, dir: NodeId, name: &str) -> Option<NodeId> {
+        dir.children(&self.arena)
+            .find(|c| self.node(*c).name == name)
+    }
+
+    fn add(&mut self, dir: NodeId, name: &str, kind: Kind) -> NodeId {
+        if let Some(existing) = self.child(dir, name) {
+            return existing;
+        }
+        let id = self.arena.new_node(Node {
+            name: name.to_string(),
+            kind,
+            total: 0,
+        });
+        dir.append(id, &mut self.arena);
+        self.sized = false;
+        id
+    }
+
+    /// The sub-directory `name` of `dir`, created if missing.
+    pub fn mkdir(&mut self, dir: NodeId, name: &str) -> NodeId {
+        self.add(dir, name, Kind::Dir)
+    }
+
+    /// The file `name` in `dir`, created with `size` if missing.
+    pub fn touch(&mut self, dir: NodeId, name: &str, size: usize) -> NodeId {
+        self.add(dir, name, Kind::File(size))
+    }
+
+    pub fn resize(&mut self, file: NodeId, size: usize) {
+        self.arena[file].get_mut().kind = Kind::File(size);
+        self.sized = false;
+    }
+
+    /// Absolute path, e.g. `/a/e`.
+    pub fn path(&self, id: NodeId) -> String {
+        let names = id
+            .ancestors(&self.arena)
+            .take_while(|a| *a != self.root)
+            .map(|a| self.node(a).name.as_str())
+            .collect::<Vec<_>>();
+        match names.is_empty() {
+            true => "/".to_string(),
+            false => names
+                .iter()
+                .rev()
+                .fold(String::new(), |acc, n| acc + "/" + n),
+        }
+    }
+
+    /// Resolves an absolute path, or one relative to `from`; `..` and `.` are understood.
+    pub fn resolve(&self, from: NodeId, path: &str) -> Option<NodeId> {
+        let start = match path.starts_with('/') {
+            true => self.root,
+            false => from,
+        };
+        path.split('/')
+            .filter(|p| !p.is_empty())
+            .try_fold(start, |cur, part| match part {
+                "." => Some(cur),
+                ".." => self.parent(cur),
+                name => self.child(cur, name),
+            })
+    }
+
+    pub fn lookup(&self, path: &str) -> Option<NodeId> {
+        self.resolve(self.root, path)
+    }
+
+    /// Caches every subtree size in a single post-order pass, each node summing its
+    /// children after they have been visited.
+    pub fn compute_sizes(&mut self) {
+        let order = self
+            .root
+            .traverse(&self.arena)
+            .filter_map(|edge| match edge {
+                NodeEdge::End(id) => Some(id),
+                NodeEdge::Start(_) => None,
+            })
+            .collect::<Vec<_>>();
+
+        for id in order {
+            let total = match self.node(id).kind {
+                Kind::File(size) => size,
+                Kind::Dir => id
+                    .children(&self.arena)
+                    .map(|c| self.arena[c].get().total)
+                    .sum(),
+            };
+            self.arena[id].get_mut().total = total;
+        }
+        self.sized = true;
+    }
+
+    /// Size of a file, or the total size of every file below a directory. Cached sizes are
+    /// used when `compute_sizes` ran after the last change; otherwise the subtree is walked.
+    pub fn size(&self, id: NodeId) -> usize {
+        if self.sized {
+            return self.node(id).total;
+        }
+        id.descendants(&self.arena)
+            .map(|d| match self.node(d).kind {
+                Kind::File(size) => size,
+                Kind::Dir => 0,
+            })
+            .sum()
+    }
+
+    pub fn du(&self, path: &str) -> Option<usize> {
+        self.lookup(path).map(|id| self.size(id))
+    }
+
+    pub fn dirs(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.root
+            .descendants(&self.arena)
+            .filter(|d| self.is_dir(*d))
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.root
+            .descendants(&self.arena)
+            .filter(|d| !self.is_dir(*d))
+    }
+
+    /// Paths of every entry named `name`.
+    pub fn find(&self, name: &str) -> Vec<String> {
+        self.root
+            .descendants(&self.arena)
+            .filter(|d| self.node(*d).name == name)
+            .map(|d| self.path(d))
+            .collect()
+    }
+
+    /// The `n` biggest files with their paths, biggest first.
+    pub fn largest_files(&self, n: usize) -> Vec<(String, usize)> {
+        let mut files = self
+            .files()
+            .map(|f| (self.path(f), self.size(f)))
+            .collect::<Vec<_>>();
+        files.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        files.truncate(n);
+        files
+    }
+
+    fn render(&self, f: &mut fmt::Formatter, id: NodeId, depth: usize) -> fmt::Result {
+        let node = self.node(id);
+        match node.kind {
+            Kind::Dir => writeln!(f, "{:1$}- {2} (dir)", "", depth * 2, node.name)?,
+            Kind::File(size) => writeln!(
+                f,
+                "{:1$}- {2} (file, size={3})",
+                "",
+                depth * 2,
+                node.name,
+                size
+            )?,
+        }
+        let mut children = id.children(&self.arena).collect::<Vec<_>>();
+        children.sort_by(|a, b| self.node(*a).name.cmp(&self.node(*b).name));
+        children
+            .into_iter()
+            .try_for_each(|c| self.render(f, c, depth + 1))
+    }
+}
+
+/// The indented listing used in the puzzle statement, entries sorted by name.
+impl fmt::Display for FileSystem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.render(f, self.root, 0)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// The filesystem of the puzzle's example, sizes computed.
+    pub(crate) fn sample() -> FileSystem {
+        let mut fs = FileSystem::new();
+        let root = fs.root();
+        let a = fs.mkdir(root, "a");
+        let e = fs.mkdir(a, "e");
+        fs.touch(e, "i", 584);
+        fs.touch(a, "f", 29116);
+        fs.touch(a, "g", 2557);
+        fs.touch(a, "h.lst", 62596);
+        fs.touch(root, "b.txt", 14848514);
+        fs.touch(root, "c.dat", 8504156);
+        let d = fs.mkdir(root, "d");
+        fs.touch(d, "j", 4060174);
+        fs.touch(d, "d.log", 8033020);
+        fs.touch(d, "d.ext", 5626152);
+        fs.touch(d, "k", 7214296);
+        fs.compute_sizes();
+        fs
+    }
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            sample().to_string(),
+            "- / (dir)
+  - a (dir)
+    - e (dir)
+      - i (file, size=584)
+    - f (file, size=29116)
+    - g (file, size=2557)
+    - h.lst (file, size=62596)
+  - b.txt (file, size=14848514)
+  - c.dat (file, size=8504156)
+  - d (dir)
+    - d.ext (file, size=5626152)
+    - d.log (file, size=8033020)
+    - j (file, size=4060174)
+    - k (file, size=7214296)
+"
+        );
+    }
+
+    #[test]
+    fn test_cached_sizes() {
+        let mut fs = sample();
+        let walked = fs.dirs().map(|d| fs.size(d)).collect::<Vec<_>>();
+        fs.compute_sizes();
+        assert_eq!(fs.dirs().map(|d| fs.size(d)).collect::<Vec<_>>(), walked);
+
+        let e = fs.lookup("/a/e").unwrap();
+        fs.touch(e, "new", 16);
+        assert_eq!(fs.du("/a"), Some(94869));
+        fs.compute_sizes();
+        assert_eq!(fs.du("/"), Some(48381181));
+    }
+
+    #[test]
+    fn test_queries() {
+        let mut fs = sample();
+        fs.compute_sizes();
+        assert_eq!(fs.du("/a/e"), Some(584));
+        assert_eq!(fs.du("/a"), Some(94853));
+        assert_eq!(fs.du("/d"), Some(24933642));
+        assert_eq!(fs.du("/"), Some(48381165));
+        assert_eq!(fs.du("/x"), None);
+        assert_eq!(fs.path(fs.lookup("a/e/../e/i").unwrap()), "/a/e/i");
+        assert_eq!(fs.find("e"), vec!["/a/e"]);
+        assert_eq!(
+            fs.largest_files(2),
+            vec![
+                ("/b.txt".to_string(), 14848514),
+                ("/c.dat".to_string(), 8504156)
+            ]
+        );
+    }
+}
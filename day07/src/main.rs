@@ -1,76 +1,18 @@
-use indextree::{Arena, NodeId};
+use day07::{
+    cleanup::{plan_deletion, Candidates, Disk},
+    shell::exec_commands,
+    FileSystem,
+};
 
-#[derive(Debug, Clone)]
-pub struct Dir {
-    name: String,
-    size: usize,
+fn part1(fs: &FileSystem) -> usize {
+    fs.dirs().map(|d| fs.size(d)).filter(|s| *s <= 100000).sum()
 }
 
-impl Dir {
-    fn new(name: &str, size: usize) -> Self {
-        Dir {
-            name: name.to_string(),
-            size,
-        }
-    }
-}
-
-fn exec_commands(input: &str) -> (NodeId, Arena<Dir>) {
-    let arena = &mut Arena::new();
-    let mut crt_dir = arena.new_node(Dir::new("/", 0));
-    let root = crt_dir;
-
-    input.trim().lines().for_each(|l| {
-        let cmd = l.split_ascii_whitespace().collect::<Vec<&str>>();
-        match cmd[..] {
-            ["$", "cd", "/"] => crt_dir = root,
-            ["$", "cd", ".."] => crt_dir = arena.get(crt_dir).unwrap().parent().unwrap(),
-            ["$", "cd", dir_name] => {
-                crt_dir = crt_dir
-                    .children(arena)
-                    .find(|c| arena.get(*c).unwrap().get().name == dir_name)
-                    .unwrap_or_else(|| {
-                        let nc = arena.new_node(Dir::new(dir_name, 0));
-                        crt_dir.append(nc, arena);
-                        nc
-                    })
-            }
-            ["$", "ls"] => (),
-            ["dir", _] => (),
-            [size, _name] => {
-                arena.get_mut(crt_dir).unwrap().get_mut().size += size.parse::<usize>().unwrap()
-            }
-            _ => panic!("Unknown command"),
-        }
-    });
-
-    (root, arena.to_owned())
-}
+fn part2(fs: &FileSystem, disk: &Disk) -> usize {
+    let needed = disk.needed(fs);
 
-fn part1(root: NodeId, arena: &Arena<Dir>) -> usize {
-    root.descendants(arena).fold(0, |acc, d| {
-        let s = d
-            .descendants(arena)
-            .fold(0, |acc, dd| acc + arena.get(dd).unwrap().get().size);
-        match s <= 100000 {
-            true => acc + s,
-            false => acc,
-        }
-    })
-}
-
-fn part2(root: NodeId, arena: &Arena<Dir>) -> usize {
-    let ocupied_size = root
-        .descendants(arena)
-        .fold(0, |acc, d| acc + arena.get(d).unwrap().get().size);
-
-    let needed = ocupied_size - 40000000;
-
-    root.descendants(arena)
-        .map(|d| {
-            d.descendants(arena)
-                .fold(0, |acc, dd| acc + arena.get(dd).unwrap().get().size)
-        })
+    fs.dirs()
+        .map(|d| fs.size(d))
         .filter(|s| *s >= needed)
         .min()
         .unwrap()
@@ -79,10 +21,39 @@ fn part2(root: NodeId, arena: &Arena<Dir>) -> usize {
 fn main() {
     let input = include_str!("input");
 
-    let (root, arena) = exec_commands(input);
-    //dbg!(root.debug_pretty_print(&arena));
-    println!("Part1: {}", part1(root, &arena));
-    println!("Part2: {}", part2(root, &arena));
+    let (fs, diagnostics) = exec_commands(input);
+    diagnostics.iter().for_each(|d| eprintln!("{}", d));
+    println!("Part1: {}", part1(&fs));
+    println!("Part2: {}", part2(&fs, &Disk::default()));
+
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        ["tree"] => print!("{}", fs),
+        ["du", path] => println!("{}", fs.du(path).expect("no such file or directory")),
+        ["find", name] => fs.find(name).iter().for_each(|p| println!("{}", p)),
+        ["largest", n] => fs
+            .largest_files(n.parse().unwrap())
+            .iter()
+            .for_each(|(p, s)| println!("{} {}", s, p)),
+        ["plan", capacity, required, candidates] => {
+            let disk = Disk {
+                capacity: capacity.parse().unwrap(),
+                required: required.parse().unwrap(),
+            };
+            let candidates = match candidates.parse::<Candidates>() {
+                Ok(candidates) => candidates,
+                Err(e) => {
+                    eprintln!("{}\nusage: plan <capacity> <required> <dirs|files|both>", e);
+                    std::process::exit(1);
+                }
+            };
+            match plan_deletion(&fs, &disk, candidates) {
+                Some(plan) => println!("{}", plan),
+                None => println!("Deleting everything would not free enough space"),
+            }
+        }
+        _ => (),
+    }
 }
 
 #[cfg(test)]
@@ -115,13 +86,13 @@ $ ls
 
     #[test]
     fn test_part1() {
-        let (root, arena) = exec_commands(INPUT);
-        assert_eq!(part1(root, &arena), 95437);
+        let (fs, _) = exec_commands(INPUT);
+        assert_eq!(part1(&fs), 95437);
     }
 
     #[test]
     fn test_part2() {
-        let (root, arena) = exec_commands(INPUT);
-        assert_eq!(part2(root, &arena), 24933642);
+        let (fs, _) = exec_commands(INPUT);
+        assert_eq!(part2(&fs, &Disk::default()), 24933642);
     }
 }
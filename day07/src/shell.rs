@@ -0,0 +1,302 @@
+use crate::{FileSystem, Kind};
+use indextree::NodeId;
+use std::{collections::HashSet, fmt};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    UnknownCommand(String),
+    MalformedEntry(String),
+    OutputWithoutLs,
+    AboveRoot,
+    NoSuchDirectory(String),
+    NotADirectory(String),
+    /// A relative `cd` or listing output after a `cd` that failed, until the next one that
+    /// works.
+    NoCurrentDirectory,
+    SizeChanged {
+        name: String,
+        was: usize,
+        now: usize,
+    },
+    KindChanged(String),
+}
+
+/// A transcript line that does not fit what came before. The interpreter carries on after
+/// reporting it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub problem: Problem,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.problem {
+            Problem::UnknownCommand(cmd) => write!(f, "unknown command {:?}", cmd),
+            Problem::MalformedEntry(entry) => write!(f, "malformed listing entry {:?}", entry),
+            Problem::OutputWithoutLs => write!(f, "output without a preceding ls"),
+            Problem::AboveRoot => write!(f, "cd .. from the root directory"),
+            Problem::NoSuchDirectory(path) => write!(f, "cd into unlisted directory {}", path),
+            Problem::NotADirectory(path) => write!(f, "cd into file {}", path),
+            Problem::NoCurrentDirectory => write!(f, "no current directory since a failed cd"),
+            Problem::SizeChanged { name, was, now } => {
+                write!(f, "{} listed with size {} after {}", name, now, was)
+            }
+            Problem::KindChanged(name) => write!(f, "{} listed as both file and directory", name),
+        }
+    }
+}
+
+struct Session {
+    fs: FileSystem,
+    /// `None` once a `cd` has failed, so that nothing is added to the wrong directory.
+    cwd: Option<NodeId>,
+    listing: bool,
+    listed: HashSet<NodeId>,
+    diagnostics: Vec<Diagnostic>,
+    line: usize,
+}
+
+impl Session {
+    fn report(&mut self, problem: Problem) {
+        self.diagnostics.push(Diagnostic {
+            line: self.line,
+            problem,
+        });
+    }
+
+    /// Follows an absolute or relative path one component at a time. A directory missing from
+    /// a listing is an error; one whose parent was never listed is assumed to exist.
+    fn cd(&mut self, path: &str) {
+        self.cwd = self.follow(path);
+    }
+
+    fn follow(&mut self, path: &str) -> Option<NodeId> {
+        let mut dir = match (path.starts_with('/'), self.cwd) {
+            (true, _) => self.fs.root(),
+            (false, Some(cwd)) => cwd,
+            (false, None) => return self.fail(Problem::NoCurrentDirectory),
+        };
+        for part in path.split('/').filter(|p| !p.is_empty() && *p != ".") {
+            let next = match part {
+                ".." => match self.fs.parent(dir) {
+                    Some(parent) => parent,
+                    None => return self.fail(Problem::AboveRoot),
+                },
+                name => match self.fs.child(dir, name) {
+                    Some(child) if self.fs.is_dir(child) => child,
+                    Some(child) => return self.fail(Problem::NotADirectory(self.fs.path(child))),
+                    None if self.listed.contains(&dir) => {
+                        let path = self.fs.path(dir);
+                        let path = path.trim_end_matches('/').to_string() + "/" + name;
+                        return self.fail(Problem::NoSuchDirectory(path));
+                    }
+                    None => self.fs.mkdir(dir, name),
+                },
+            };
+            dir = next;
+        }
+        Some(dir)
+    }
+
+    fn fail(&mut self, problem: Problem) -> Option<NodeId> {
+        self.report(problem);
+        None
+    }
+
+    fn entry(&mut self, dir: NodeId, name: &str, kind: Kind) {
+        let existing = self.fs.child(dir, name);
+        match (existing.map(|e| self.fs.node(e).kind.clone()), kind) {
+            (None, Kind::Dir) => {
+                self.fs.mkdir(dir, name);
+            }
+            (None, Kind::File(size)) => {
+                self.fs.touch(dir, name, size);
+            }
+            (Some(Kind::Dir), Kind::Dir) => (),
+            (Some(Kind::File(was)), Kind::File(now)) if was == now => (),
+            (Some(Kind::File(was)), Kind::File(now)) => {
+                self.fs.resize(existing.unwrap(), now);
+                self.report(Problem::SizeChanged {
+                    name: name.to_string(),
+                    was,
+                    now,
+                });
+            }
+            _ => self.report(Problem::KindChanged(name.to_string())),
+        }
+    }
+
+    fn exec(&mut self, line: &str) {
+        let tokens = line.split_ascii_whitespace().collect::<Vec<&str>>();
+        if tokens.first() == Some(&"$") {
+            self.listing = false;
+        }
+        match tokens[..] {
+            [] => (),
+            ["$", "cd", path] => self.cd(path),
+            ["$", "ls"] => {
+                self.listing = true;
+                self.listed.extend(self.cwd);
+            }
+            ["$", ..] => self.report(Problem::UnknownCommand(line.to_string())),
+            _ if !self.listing => self.report(Problem::OutputWithoutLs),
+            _ if self.cwd.is_none() => self.report(Problem::NoCurrentDirectory),
+            ["dir", name] => self.entry(self.cwd.unwrap(), name, Kind::Dir),
+            [size, name] => match size.parse() {
+                Ok(size) => self.entry(self.cwd.unwrap(), name, Kind::File(size)),
+                Err(_) => self.report(Problem::MalformedEntry(line.to_string())),
+            },
+            _ => self.report(Problem::MalformedEntry(line.to_string())),
+        }
+    }
+}
+
+/// Replays a shell transcript into a filesystem, with every inconsistency found on the way.
+/// Listing a directory again only adds what is new.
+pub fn exec_commands(input: &str) -> (FileSystem, Vec<Diagnostic>) {
+    let fs = FileSystem::new();
+    let mut session = Session {
+        cwd: Some(fs.root()),
+        fs,
+        listing: false,
+        listed: HashSet::new(),
+        diagnostics: Vec::new(),
+        line: 0,
+    };
+
+    for (i, line) in input.lines().enumerate() {
+        session.line = i + 1;
+        session.exec(line);
+    }
+
+    session.fs.compute_sizes();
+    (session.fs, session.diagnostics)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problems(input: &str) -> Vec<(usize, Problem)> {
+        exec_commands(input)
+            .1
+            .into_iter()
+            .map(|d| (d.line, d.problem))
+            .collect()
+    }
+
+    #[test]
+    fn test_paths() {
+        let (fs, diagnostics) = exec_commands(
+            "$ cd /a/b
+$ ls
+10 x
+$ cd ../../c
+$ ls
+20 y
+$ cd /
+$ cd a/./b/..
+$ ls
+dir b
+5 z",
+        );
+        assert!(diagnostics.is_empty());
+        assert_eq!(fs.du("/a"), Some(15));
+        assert_eq!(fs.du("/c/y"), Some(20));
+        assert_eq!(fs.du("/"), Some(35));
+    }
+
+    #[test]
+    fn test_repeated_listing() {
+        let (fs, diagnostics) = exec_commands(
+            "$ cd /
+$ ls
+dir a
+10 x
+$ ls
+dir a
+10 x
+7 y",
+        );
+        assert!(diagnostics.is_empty());
+        assert_eq!(fs.du("/"), Some(17));
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        assert_eq!(
+            problems(
+                "$ cd ..
+$ cd /
+$ ls
+dir a
+10 x
+$ cd b
+$ cd /x
+$ cd /
+12 x
+$ ls
+12 x
+x
+dir x
+$ rm x
+$ cd a
+$ ls
+abc def"
+            ),
+            vec![
+                (1, Problem::AboveRoot),
+                (6, Problem::NoSuchDirectory("/b".to_string())),
+                (7, Problem::NotADirectory("/x".to_string())),
+                (9, Problem::OutputWithoutLs),
+                (
+                    11,
+                    Problem::SizeChanged {
+                        name: "x".to_string(),
+                        was: 10,
+                        now: 12
+                    }
+                ),
+                (12, Problem::MalformedEntry("x".to_string())),
+                (13, Problem::KindChanged("x".to_string())),
+                (14, Problem::UnknownCommand("$ rm x".to_string())),
+                (17, Problem::MalformedEntry("abc def".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_failed_cd() {
+        let input = "$ cd /
+$ ls
+dir a
+10 x
+$ cd missing
+$ ls
+dir b
+20 y
+$ cd a
+$ cd /a
+$ ls
+5 z";
+        let (fs, diagnostics) = exec_commands(input);
+        let problems = diagnostics
+            .into_iter()
+            .map(|d| (d.line, d.problem))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            problems,
+            vec![
+                (5, Problem::NoSuchDirectory("/missing".to_string())),
+                (7, Problem::NoCurrentDirectory),
+                (8, Problem::NoCurrentDirectory),
+                (9, Problem::NoCurrentDirectory),
+            ]
+        );
+        assert_eq!(fs.du("/"), Some(15));
+        assert_eq!(fs.du("/a"), Some(5));
+        assert_eq!(fs.du("/b"), None);
+    }
+}
@@ -0,0 +1,245 @@
+pub mod map;
+pub mod placement;
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Tree heights of a rectangular forest, row by row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Forest {
+    pub rows: usize,
+    pub cols: usize,
+    heights: Vec<u8>,
+}
+
+impl Forest {
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut heights = Vec::new();
+        let mut cols = None;
+        let mut rows = 0;
+        for (i, line) in input.trim().lines().enumerate() {
+            let err = |message: String| ParseError {
+                line: i + 1,
+                message,
+            };
+            let before = heights.len();
+            for c in line.trim().chars() {
+                let h = c
+                    .to_digit(10)
+                    .ok_or_else(|| err(format!("{:?} is not a tree height", c)))?;
+                heights.push(h as u8);
+            }
+            let len = heights.len() - before;
+            match cols {
+                Some(cols) if cols != len => {
+                    return Err(err(format!("{} trees in a row of {}", len, cols)))
+                }
+                _ => cols = Some(len),
+            }
+            rows += 1;
+        }
+        Ok(Forest {
+            rows,
+            cols: cols.unwrap_or(0),
+            heights,
+        })
+    }
+
+    pub fn height(&self, r: usize, c: usize) -> u8 {
+        self.heights[r * self.cols + c]
+    }
+
+    pub fn len(&self) -> usize {
+        self.heights.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heights.is_empty()
+    }
+}
+
+/// For every tree, the edges it can be seen from and how far it sees in each direction.
+#[derive(Debug, Clone)]
+pub struct Views {
+    rows: usize,
+    cols: usize,
+    visible: Vec<u8>,
+    distance: Vec<[usize; 4]>,
+}
+
+impl Views {
+    /// One pass per direction along every row and column. A stack holds the trees passed so
+    /// far that no later tree has hidden yet, tallest at the bottom; the first one at least
+    /// as tall as the current tree blocks its view, and an empty stack means it sees the edge.
+    pub fn new(forest: &Forest) -> Self {
+        let mut views = Views {
+            rows: forest.rows,
+            cols: forest.cols,
+            visible: vec![0; forest.len()],
+            distance: vec![[0; 4]; forest.len()],
+        };
+        let (rows, cols) = (forest.rows, forest.cols);
+        for r in 0..rows {
+            views.sweep(forest, Direction::Left, (0..cols).map(|c| r * cols + c));
+            views.sweep(
+                forest,
+                Direction::Right,
+                (0..cols).rev().map(|c| r * cols + c),
+            );
+        }
+        for c in 0..cols {
+            views.sweep(forest, Direction::Up, (0..rows).map(|r| r * cols + c));
+            views.sweep(
+                forest,
+                Direction::Down,
+                (0..rows).rev().map(|r| r * cols + c),
+            );
+        }
+        views
+    }
+
+    fn sweep(&mut self, forest: &Forest, from: Direction, line: impl Iterator<Item = usize>) {
+        let mut stack: Vec<(usize, u8)> = Vec::new();
+        for (k, idx) in line.enumerate() {
+            let h = forest.heights[idx];
+            while stack.last().is_some_and(|(_, top)| *top < h) {
+                stack.pop();
+            }
+            self.distance[idx][from as usize] = match stack.last() {
+                Some((blocker, _)) => k - blocker,
+                None => {
+                    self.visible[idx] |= from.bit();
+                    k
+                }
+            };
+            stack.push((k, h));
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn is_visible(&self, r: usize, c: usize) -> bool {
+        self.visible[r * self.cols + c] != 0
+    }
+
+    pub fn visible_from(&self, r: usize, c: usize, from: Direction) -> bool {
+        self.visible[r * self.cols + c] & from.bit() != 0
+    }
+
+    /// The edges the tree at `(r, c)` can be seen from.
+    pub fn visible_directions(&self, r: usize, c: usize) -> Vec<Direction> {
+        Direction::ALL
+            .into_iter()
+            .filter(|d| self.visible_from(r, c, *d))
+            .collect()
+    }
+
+    /// Trees seen towards `towards`, up to and including the first one blocking the view.
+    pub fn distance(&self, r: usize, c: usize, towards: Direction) -> usize {
+        self.distance[r * self.cols + c][towards as usize]
+    }
+
+    pub fn scenic_score(&self, r: usize, c: usize) -> usize {
+        self.distance[r * self.cols + c].iter().product()
+    }
+
+    pub fn visible_count(&self) -> usize {
+        self.visible.iter().filter(|v| **v != 0).count()
+    }
+
+    pub fn best_score(&self) -> usize {
+        self.best().map_or(0, |(_, _, score)| score)
+    }
+
+    /// Row, column and scenic score of the best placed tree, the first in reading order on ties.
+    pub fn best(&self) -> Option<(usize, usize, usize)> {
+        (0..self.rows)
+            .flat_map(|r| (0..self.cols).map(move |c| (r, c)))
+            .map(|(r, c)| (r, c, self.scenic_score(r, c)))
+            .rev()
+            .max_by_key(|(_, _, score)| *score)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// The puzzle's example forest.
+    pub(crate) fn sample() -> Forest {
+        Forest::parse("30373\n25512\n65332\n33549\n35390").unwrap()
+    }
+
+    #[test]
+    fn test_rectangular() {
+        let forest = Forest::parse("30373\n25512\n65332").unwrap();
+        assert_eq!((forest.rows, forest.cols), (3, 5));
+        let views = Views::new(&forest);
+        assert_eq!(views.visible_count(), 14);
+        assert!(views.visible_from(1, 1, Direction::Left));
+        assert!(views.visible_from(1, 1, Direction::Up));
+        assert!(!views.visible_from(1, 1, Direction::Right));
+        assert_eq!(views.distance(1, 2, Direction::Left), 1);
+        assert_eq!(views.distance(1, 2, Direction::Right), 2);
+        assert_eq!(views.scenic_score(1, 2), 2);
+
+        let tall = Forest::parse("3\n0\n5\n1\n1").unwrap();
+        let views = Views::new(&tall);
+        assert_eq!(views.visible_count(), 5);
+        assert_eq!(views.distance(3, 0, Direction::Up), 1);
+        assert_eq!(views.distance(1, 0, Direction::Down), 1);
+        assert_eq!(views.best_score(), 0);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Forest::parse("123\n12"),
+            Err(ParseError {
+                line: 2,
+                message: "2 trees in a row of 3".to_string()
+            })
+        );
+        assert!(Forest::parse("12a").is_err());
+    }
+}
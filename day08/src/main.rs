@@ -1,106 +1,68 @@
-use itertools::FoldWhile::{Continue, Done};
-use itertools::Itertools;
+use day08::{
+    map::{Layer, Map},
+    placement::{best_sites, visible_from_height, Constraints},
+    Direction, Forest, Views,
+};
 
-fn parse_input(input: &str) -> Vec<Vec<u8>> {
-    input
-        .trim()
-        .lines()
-        .map(|l| l.chars().map(|c| c.to_digit(10).unwrap() as u8).collect())
-        .collect()
+fn parse_input(input: &str) -> Forest {
+    Forest::parse(input).unwrap()
 }
 
-fn part1(grid: &Vec<Vec<u8>>) -> usize {
-    let mut counter = 0_usize;
-    for r in 1..grid[0].len() - 1 {
-        for c in 1..grid.len() - 1 {
-            let crt = grid[r][c];
-            //Left
-            if !(0..c).rev().any(|i| grid[r][i] >= crt) {
-                counter += 1;
-                continue;
-            }
-            //Right
-            if !(c + 1..grid[0].len()).any(|i| grid[r][i] >= crt) {
-                counter += 1;
-                continue;
-            }
-            //Up
-            if !(0..r).rev().any(|i| grid[i][c] >= crt) {
-                counter += 1;
-                continue;
-            }
-            //Down
-            if !(r + 1..grid.len()).any(|i| grid[i][c] >= crt) {
-                counter += 1;
-                continue;
-            }
-        }
-    }
-
-    //Add the edges
-    counter + 2 * (grid[0].len() + grid.len() - 2)
+fn part1(forest: &Forest) -> usize {
+    Views::new(forest).visible_count()
 }
 
-fn part2(grid: &Vec<Vec<u8>>) -> usize {
-    let mut highest = 0_usize;
-    for r in 1..grid[0].len() - 1 {
-        for c in 1..grid.len() - 1 {
-            let crt = grid[r][c];
-
-            let left = (0..c)
-                .rev()
-                .fold_while(0, |acc, i| {
-                    if grid[r][i] >= crt {
-                        Done(acc + 1)
-                    } else {
-                        Continue(acc + 1)
-                    }
-                })
-                .into_inner() as usize;
-
-            let right = (c + 1..grid[0].len())
-                .fold_while(0, |acc, i| {
-                    if grid[r][i] >= crt {
-                        Done(acc + 1)
-                    } else {
-                        Continue(acc + 1)
-                    }
-                })
-                .into_inner() as usize;
-
-            let up = (0..r)
-                .rev()
-                .fold_while(0, |acc, i| {
-                    if grid[i][c] >= crt {
-                        Done(acc + 1)
-                    } else {
-                        Continue(acc + 1)
-                    }
-                })
-                .into_inner() as usize;
-
-            let down = (r + 1..grid.len())
-                .fold_while(0, |acc, i| {
-                    if grid[i][c] >= crt {
-                        Done(acc + 1)
-                    } else {
-                        Continue(acc + 1)
-                    }
-                })
-                .into_inner() as usize;
-
-            //println!("Score for [{}][{}] = {}", r, c, left * right * up * down);
-            highest = std::cmp::max(highest, left * right * up * down);
-        }
-    }
-
-    highest
+fn part2(forest: &Forest) -> usize {
+    Views::new(forest).best_score()
 }
 
 fn main() {
     let grid = parse_input(include_str!("input"));
     println!("{:?}", part1(&grid));
     println!("{:?}", part2(&grid));
+
+    let views = Views::new(&grid);
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let layer = |name: &str| match name.parse::<Layer>() {
+        Ok(layer) => layer,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    let print = |map: Map, format: &str| match format {
+        "pgm" => print!("{}", map.pgm()),
+        _ => print!("{}", map.heatmap()),
+    };
+    match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        ["best"] => {
+            if let Some((r, c, score)) = views.best() {
+                println!(
+                    "Best tree at row {} column {}, scenic score {}",
+                    r, c, score
+                );
+                for d in Direction::ALL {
+                    println!("  {:?}: {} trees", d, views.distance(r, c, d));
+                }
+            }
+        }
+        ["map", name] => print(views.map(layer(name)), "txt"),
+        ["map", name, format] => print(views.map(layer(name)), format),
+        ["sites", k, min_height] => {
+            let constraints = Constraints {
+                min_height: min_height.parse().unwrap(),
+                ..Default::default()
+            };
+            for site in best_sites(&grid, &views, &constraints, k.parse().unwrap()) {
+                println!(
+                    "row {} column {} height {}: {}",
+                    site.row, site.col, site.height, site.score
+                );
+            }
+        }
+        ["outside", eye] => println!("{}", visible_from_height(&grid, eye.parse().unwrap())),
+        _ => (),
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,177 @@
+use crate::{Direction, Views};
+use std::{fmt::Write, str::FromStr};
+
+/// Shades for text heatmaps, from the lowest value to the highest.
+const RAMP: &[u8] = b" .:-=+*#%@";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layer {
+    /// How many edges a tree can be seen from, 0 to 4.
+    Visibility,
+    Distance(Direction),
+    Score,
+}
+
+impl Layer {
+    /// Every layer with the name it is given on the command line.
+    pub const NAMES: [(&'static str, Layer); 6] = [
+        ("visible", Layer::Visibility),
+        ("up", Layer::Distance(Direction::Up)),
+        ("down", Layer::Distance(Direction::Down)),
+        ("left", Layer::Distance(Direction::Left)),
+        ("right", Layer::Distance(Direction::Right)),
+        ("score", Layer::Score),
+    ];
+}
+
+impl FromStr for Layer {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Layer::NAMES
+            .iter()
+            .find(|(name, _)| *name == s)
+            .map(|(_, layer)| *layer)
+            .ok_or_else(|| {
+                let names = Layer::NAMES.map(|(name, _)| name);
+                format!(
+                    "invalid layer {:?}, expected one of {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// One value per tree, row by row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Map {
+    pub rows: usize,
+    pub cols: usize,
+    pub values: Vec<usize>,
+}
+
+impl Views {
+    pub fn map(&self, layer: Layer) -> Map {
+        let values = (0..self.rows())
+            .flat_map(|r| (0..self.cols()).map(move |c| (r, c)))
+            .map(|(r, c)| match layer {
+                Layer::Visibility => self.visible_directions(r, c).len(),
+                Layer::Distance(towards) => self.distance(r, c, towards),
+                Layer::Score => self.scenic_score(r, c),
+            })
+            .collect();
+        Map {
+            rows: self.rows(),
+            cols: self.cols(),
+            values,
+        }
+    }
+}
+
+impl Map {
+    pub fn get(&self, r: usize, c: usize) -> usize {
+        self.values[r * self.cols + c]
+    }
+
+    pub fn max(&self) -> usize {
+        self.values.iter().copied().max().unwrap_or(0)
+    }
+
+    /// `value` scaled from `0..=max` onto `0..=levels`, rounding to the nearest level.
+    fn scale(&self, value: usize, levels: usize) -> usize {
+        match self.max() {
+            0 => 0,
+            max => (value * levels + max / 2) / max,
+        }
+    }
+
+    /// One character per tree, darker for higher values.
+    pub fn heatmap(&self) -> String {
+        let mut out = String::new();
+        for row in self.values.chunks(self.cols.max(1)) {
+            for value in row {
+                out.push(RAMP[self.scale(*value, RAMP.len() - 1)] as char);
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// A plain (P2) greymap, brighter for higher values. Values are scaled down to 8 bits
+    /// when the maximum does not fit.
+    pub fn pgm(&self) -> String {
+        let max = self.max();
+        let depth = max.clamp(1, 255);
+        let mut out = format!("P2\n{} {}\n{}\n", self.cols, self.rows, depth);
+        for row in self.values.chunks(self.cols.max(1)) {
+            let line = row
+                .iter()
+                .map(|v| match max > depth {
+                    true => self.scale(*v, depth),
+                    false => *v,
+                })
+                .fold(String::new(), |mut line, v| {
+                    if !line.is_empty() {
+                        line.push(' ');
+                    }
+                    write!(line, "{}", v).unwrap();
+                    line
+                });
+            writeln!(out, "{}", line).unwrap();
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Views {
+        Views::new(&crate::tests::sample())
+    }
+
+    #[test]
+    fn test_layers() {
+        let views = sample();
+        assert_eq!(views.best(), Some((3, 2, 8)));
+        assert_eq!(
+            views.visible_directions(1, 1),
+            vec![Direction::Up, Direction::Left]
+        );
+        assert_eq!(views.map(Layer::Score).values[5..10], [0, 1, 4, 1, 0]);
+        assert_eq!(views.map(Layer::Distance(Direction::Up)).get(3, 2), 2);
+        assert_eq!(views.map(Layer::Visibility).get(2, 2), 0);
+    }
+
+    #[test]
+    fn test_layer_names() {
+        assert_eq!("left".parse(), Ok(Layer::Distance(Direction::Left)));
+        assert_eq!("score".parse(), Ok(Layer::Score));
+        assert_eq!(
+            "height".parse::<Layer>(),
+            Err(
+                "invalid layer \"height\", expected one of visible, up, down, left, right, score"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_export() {
+        let map = sample().map(Layer::Score);
+        assert_eq!(map.heatmap(), "     \n .+. \n #.: \n .@- \n     \n");
+        assert_eq!(
+            map.pgm(),
+            "P2\n5 5\n8\n0 0 0 0 0\n0 1 4 1 0\n0 6 1 2 0\n0 1 8 3 0\n0 0 0 0 0\n"
+        );
+
+        let wide = Map {
+            rows: 1,
+            cols: 2,
+            values: vec![1000, 500],
+        };
+        assert_eq!(wide.pgm(), "P2\n2 1\n255\n255 128\n");
+    }
+}
@@ -0,0 +1,129 @@
+use crate::{Forest, Views};
+
+/// Rows `top..=bottom` and columns `left..=right`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Area {
+    pub top: usize,
+    pub left: usize,
+    pub bottom: usize,
+    pub right: usize,
+}
+
+impl Area {
+    pub fn contains(&self, r: usize, c: usize) -> bool {
+        (self.top..=self.bottom).contains(&r) && (self.left..=self.right).contains(&c)
+    }
+}
+
+/// Which trees may hold a treehouse. The default allows every tree.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Constraints {
+    pub min_height: u8,
+    pub area: Option<Area>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Site {
+    pub row: usize,
+    pub col: usize,
+    pub height: u8,
+    pub score: usize,
+}
+
+/// The `k` allowed trees with the highest scenic scores, best first and in reading order on
+/// ties. Scores still count the view over the whole forest, not just the allowed area.
+pub fn best_sites(
+    forest: &Forest,
+    views: &Views,
+    constraints: &Constraints,
+    k: usize,
+) -> Vec<Site> {
+    let mut sites = (0..forest.rows)
+        .flat_map(|r| (0..forest.cols).map(move |c| (r, c)))
+        .filter(|(r, c)| constraints.area.is_none_or(|a| a.contains(*r, *c)))
+        .filter(|(r, c)| forest.height(*r, *c) >= constraints.min_height)
+        .map(|(row, col)| Site {
+            row,
+            col,
+            height: forest.height(row, col),
+            score: views.scenic_score(row, col),
+        })
+        .collect::<Vec<_>>();
+    sites.sort_by_key(|s| std::cmp::Reverse(s.score));
+    sites.truncate(k);
+    sites
+}
+
+/// Trees seen from outside the forest by a viewer whose eyes are at height `eye`. The viewer
+/// looks over every tree lower than their eyes, so a tree is hidden only by one in front of
+/// it at least as tall as both the tree and the viewer. An `eye` of 0 is the ground-level
+/// count of part 1.
+pub fn visible_from_height(forest: &Forest, eye: u8) -> usize {
+    let (rows, cols) = (forest.rows, forest.cols);
+    let mut visible = vec![false; forest.len()];
+    let mut sweep = |line: &mut dyn Iterator<Item = (usize, usize)>| {
+        let mut tallest = None;
+        for (r, c) in line {
+            let h = forest.height(r, c);
+            if tallest.is_none_or(|t| t < h.max(eye)) {
+                visible[r * cols + c] = true;
+            }
+            tallest = tallest.max(Some(h));
+        }
+    };
+    for r in 0..rows {
+        sweep(&mut (0..cols).map(|c| (r, c)));
+        sweep(&mut (0..cols).rev().map(|c| (r, c)));
+    }
+    for c in 0..cols {
+        sweep(&mut (0..rows).map(|r| (r, c)));
+        sweep(&mut (0..rows).rev().map(|r| (r, c)));
+    }
+    visible.iter().filter(|v| **v).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::sample;
+
+    #[test]
+    fn test_best_sites() {
+        let forest = sample();
+        let views = Views::new(&forest);
+        let scores = |constraints: &Constraints, k| {
+            best_sites(&forest, &views, constraints, k)
+                .iter()
+                .map(|s| (s.row, s.col, s.score))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            scores(&Constraints::default(), 3),
+            vec![(3, 2, 8), (2, 1, 6), (1, 2, 4)]
+        );
+        let tall = Constraints {
+            min_height: 6,
+            ..Default::default()
+        };
+        assert_eq!(scores(&tall, 2), vec![(0, 3, 0), (2, 0, 0)]);
+        let top_left = Constraints {
+            min_height: 0,
+            area: Some(Area {
+                top: 0,
+                left: 0,
+                bottom: 2,
+                right: 1,
+            }),
+        };
+        assert_eq!(scores(&top_left, 1), vec![(2, 1, 6)]);
+        assert_eq!(scores(&Constraints::default(), 100).len(), 25);
+    }
+
+    #[test]
+    fn test_visible_from_height() {
+        let forest = sample();
+        assert_eq!(visible_from_height(&forest, 0), 21);
+        assert_eq!(visible_from_height(&forest, 4), 24);
+        assert_eq!(visible_from_height(&forest, 9), 25);
+    }
+}
@@ -0,0 +1,57 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use day09::{fast::tail_visits, Direction, Motion, Rope};
+
+/// About `steps` head steps in straight runs of 1 to 20 cells, from a fixed linear
+/// congruential sequence so every run sees the same input.
+fn motions(steps: usize) -> Vec<Motion> {
+    const DIRECTIONS: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+    let mut state = 0x2022_1209u64;
+    let mut total = 0;
+    let mut motions = Vec::new();
+    while total < steps {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+        let r = (state >> 33) as usize;
+        let motion = Motion {
+            direction: DIRECTIONS[r % 4],
+            steps: 1 + r / 4 % 20,
+        };
+        total += motion.steps;
+        motions.push(motion);
+    }
+    motions
+}
+
+fn with_rope(motions: &[Motion], knots: usize) -> usize {
+    let mut rope = Rope::new(knots, 1);
+    motions.iter().for_each(|m| rope.apply(m));
+    rope.tail_trail().len()
+}
+
+fn bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("rope");
+    group.sample_size(10);
+    for steps in [100_000, 20_000_000] {
+        let motions = motions(steps);
+        for knots in [10, 1_000] {
+            let id = format!("{}x{}", steps, knots);
+            if steps <= 100_000 {
+                assert_eq!(with_rope(&motions, knots), tail_visits(&motions, knots, 1));
+                group.bench_with_input(BenchmarkId::new("rope", &id), &motions, |b, m| {
+                    b.iter(|| with_rope(black_box(m), knots))
+                });
+            }
+            group.bench_with_input(BenchmarkId::new("fast", &id), &motions, |b, m| {
+                b.iter(|| tail_visits(black_box(m), knots, 1))
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
@@ -0,0 +1,131 @@
+use crate::{Motion, Point};
+use std::collections::HashMap;
+
+/// Side of the square tiles `Visited` is made of; a tile is one bit per cell in 64 words.
+const TILE: isize = 64;
+
+/// A set of cells stored as 64×64 bit tiles, allocated as the cells get visited.
+#[derive(Debug, Clone, Default)]
+pub struct Visited {
+    index: HashMap<(isize, isize), usize>,
+    tiles: Vec<[u64; TILE as usize]>,
+    /// The tile of the previous insertion, as consecutive cells tend to share one.
+    last: Option<((isize, isize), usize)>,
+    len: usize,
+}
+
+impl Visited {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a cell, telling whether it was new.
+    pub fn insert(&mut self, p: Point) -> bool {
+        let key = (p.x.div_euclid(TILE), p.y.div_euclid(TILE));
+        let tile = match self.last {
+            Some((last, tile)) if last == key => tile,
+            _ => {
+                let next = self.tiles.len();
+                let tile = *self.index.entry(key).or_insert(next);
+                if tile == next {
+                    self.tiles.push([0; TILE as usize]);
+                }
+                self.last = Some((key, tile));
+                tile
+            }
+        };
+        let (row, bit) = (p.y.rem_euclid(TILE) as usize, p.x.rem_euclid(TILE));
+        let word = &mut self.tiles[tile][row];
+        let new = *word & 1 << bit == 0;
+        *word |= 1 << bit;
+        self.len += new as usize;
+        new
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+/// How many cells the tail of a `len`-knot rope visits, without keeping any other trail.
+///
+/// Same rules as `Rope`, but each head step walks down the rope only until a knot stays put:
+/// the knots after it cannot move either, and when that happens before the tail there is
+/// nothing new to record.
+pub fn tail_visits(motions: &[Motion], len: usize, slack: usize) -> usize {
+    assert!(len > 0, "a rope needs at least one knot");
+    let slack = slack as isize;
+    let mut knots = vec![Point::new(0, 0); len];
+    let mut visited = Visited::new();
+    visited.insert(knots[0]);
+
+    for motion in motions {
+        let (dx, dy) = motion.direction.delta();
+        for _ in 0..motion.steps {
+            knots[0].x += dx;
+            knots[0].y += dy;
+            let mut moved = 1;
+            while moved < len {
+                let lead = knots[moved - 1];
+                let knot = &mut knots[moved];
+                let (gx, gy) = (lead.x - knot.x, lead.y - knot.y);
+                if gx.abs() <= slack && gy.abs() <= slack {
+                    break;
+                }
+                knot.x += gx.signum();
+                knot.y += gy.signum();
+                moved += 1;
+            }
+            if moved == len {
+                visited.insert(knots[len - 1]);
+            }
+        }
+    }
+    visited.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Direction, Rope};
+
+    /// Motions from a fixed linear congruential sequence, diagonals included.
+    fn motions(count: usize, seed: u64) -> Vec<Motion> {
+        const DIRECTIONS: [&str; 8] = ["U", "D", "L", "R", "UL", "UR", "DL", "DR"];
+        let mut state = seed;
+        (0..count)
+            .map(|_| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+                let r = (state >> 33) as usize;
+                Motion {
+                    direction: DIRECTIONS[r % 8].parse::<Direction>().unwrap(),
+                    steps: 1 + r / 8 % 20,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_matches_rope() {
+        for (seed, len, slack) in [(1, 1, 1), (2, 2, 1), (3, 10, 1), (4, 10, 3), (5, 50, 1)] {
+            let motions = motions(500, seed);
+            let mut rope = Rope::new(len, slack);
+            motions.iter().for_each(|m| rope.apply(m));
+            assert_eq!(tail_visits(&motions, len, slack), rope.tail_trail().len());
+        }
+    }
+
+    #[test]
+    fn test_visited() {
+        let mut visited = Visited::new();
+        assert!(visited.insert(Point::new(-1, -1)));
+        assert!(visited.insert(Point::new(63, 0)));
+        assert!(visited.insert(Point::new(64, 0)));
+        assert!(!visited.insert(Point::new(-1, -1)));
+        assert_eq!(visited.len(), 3);
+    }
+}
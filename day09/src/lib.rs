@@ -0,0 +1,246 @@
+pub mod fast;
+pub mod render;
+
+use std::{collections::HashSet, fmt, str::FromStr};
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Point {
+    pub x: isize,
+    pub y: isize,
+}
+
+impl Point {
+    pub fn new(x: isize, y: isize) -> Self {
+        Point { x, y }
+    }
+
+    /// Chebyshev distance, so diagonal neighbours are 1 apart.
+    pub fn distance(&self, other: &Point) -> usize {
+        self.x.abs_diff(other.x).max(self.y.abs_diff(other.y))
+    }
+
+    pub fn advance(&mut self, direction: Direction) {
+        let (dx, dy) = direction.delta();
+        self.x += dx;
+        self.y += dy;
+    }
+
+    /// Steps one cell towards `other` if it is more than `slack` away, telling whether it moved.
+    pub fn follow(&mut self, other: &Point, slack: usize) -> bool {
+        if self.distance(other) <= slack {
+            return false;
+        }
+        self.x += isize::signum(other.x - self.x);
+        self.y += isize::signum(other.y - self.y);
+        true
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
+}
+
+impl Direction {
+    /// Unit move with `y` growing upwards.
+    pub fn delta(self) -> (isize, isize) {
+        match self {
+            Direction::Up => (0, 1),
+            Direction::Down => (0, -1),
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+            Direction::UpLeft => (-1, 1),
+            Direction::UpRight => (1, 1),
+            Direction::DownLeft => (-1, -1),
+            Direction::DownRight => (1, -1),
+        }
+    }
+}
+
+impl FromStr for Direction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "U" => Ok(Direction::Up),
+            "D" => Ok(Direction::Down),
+            "L" => Ok(Direction::Left),
+            "R" => Ok(Direction::Right),
+            "UL" => Ok(Direction::UpLeft),
+            "UR" => Ok(Direction::UpRight),
+            "DL" => Ok(Direction::DownLeft),
+            "DR" => Ok(Direction::DownRight),
+            _ => Err(format!("invalid direction {:?}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Motion {
+    pub direction: Direction,
+    pub steps: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+pub fn parse_input(input: &str) -> Result<Vec<Motion>, ParseError> {
+    input
+        .trim()
+        .lines()
+        .enumerate()
+        .map(|(i, l)| {
+            let err = |message: String| ParseError {
+                line: i + 1,
+                message,
+            };
+            let (d, c) = l
+                .trim()
+                .split_once(' ')
+                .ok_or_else(|| err(format!("expected a direction and a count in {:?}", l)))?;
+            Ok(Motion {
+                direction: d.parse().map_err(err)?,
+                steps: c
+                    .parse()
+                    .map_err(|_| err(format!("invalid count {:?}", c)))?,
+            })
+        })
+        .collect()
+}
+
+/// A rope of knots, each following the one before it, and every cell each knot has been in.
+#[derive(Debug, Clone)]
+pub struct Rope {
+    knots: Vec<Point>,
+    slack: usize,
+    trails: Vec<HashSet<Point>>,
+}
+
+impl Rope {
+    /// `len` knots at the origin. A knot moves once it is more than `slack` cells away from
+    /// the one it follows; the puzzle's rope has a slack of 1.
+    pub fn new(len: usize, slack: usize) -> Self {
+        assert!(len > 0, "a rope needs at least one knot");
+        let knots = vec![Point::new(0, 0); len];
+        Rope {
+            trails: knots.iter().map(|k| HashSet::from([*k])).collect(),
+            knots,
+            slack,
+        }
+    }
+
+    pub fn knots(&self) -> &[Point] {
+        &self.knots
+    }
+
+    pub fn head(&self) -> Point {
+        self.knots[0]
+    }
+
+    pub fn tail(&self) -> Point {
+        self.knots[self.knots.len() - 1]
+    }
+
+    pub fn slack(&self) -> usize {
+        self.slack
+    }
+
+    /// Cells visited by knot `i`, the head being 0.
+    pub fn trail(&self, i: usize) -> &HashSet<Point> {
+        &self.trails[i]
+    }
+
+    pub fn tail_trail(&self) -> &HashSet<Point> {
+        &self.trails[self.knots.len() - 1]
+    }
+
+    /// Moves the head one cell and lets the rest of the rope follow.
+    pub fn step(&mut self, direction: Direction) {
+        self.knots[0].advance(direction);
+        self.trails[0].insert(self.knots[0]);
+        for i in 1..self.knots.len() {
+            let leader = self.knots[i - 1];
+            if self.knots[i].follow(&leader, self.slack) {
+                self.trails[i].insert(self.knots[i]);
+            }
+        }
+    }
+
+    pub fn apply(&mut self, motion: &Motion) {
+        for _ in 0..motion.steps {
+            self.step(motion.direction);
+        }
+    }
+
+    /// Runs `motions` one head step at a time, yielding the knots after each step.
+    pub fn steps<'a>(&'a mut self, motions: &'a [Motion]) -> impl Iterator<Item = Vec<Point>> + 'a {
+        motions
+            .iter()
+            .flat_map(|m| std::iter::repeat_n(m.direction, m.steps))
+            .map(move |d| {
+                self.step(d);
+                self.knots.clone()
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagonal_moves() {
+        let motions = parse_input("UR 3\nDL 1\nR 1").unwrap();
+        let mut rope = Rope::new(2, 1);
+        let states = rope.steps(&motions).collect::<Vec<_>>();
+        assert_eq!(states[0], vec![Point::new(1, 1), Point::new(0, 0)]);
+        assert_eq!(states[2], vec![Point::new(3, 3), Point::new(2, 2)]);
+        assert_eq!(states[4], vec![Point::new(3, 2), Point::new(2, 2)]);
+        assert_eq!(rope.trail(0).len(), 5);
+        assert_eq!(rope.tail_trail().len(), 3);
+    }
+
+    #[test]
+    fn test_slack() {
+        let motions = parse_input("R 5").unwrap();
+        let mut rope = Rope::new(3, 2);
+        motions.iter().for_each(|m| rope.apply(m));
+        assert_eq!(
+            rope.knots(),
+            [Point::new(5, 0), Point::new(3, 0), Point::new(1, 0)]
+        );
+        assert_eq!(rope.trail(1).len(), 4);
+        assert_eq!(rope.tail_trail().len(), 2);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse_input("R 4\nX 2").unwrap_err(),
+            ParseError {
+                line: 2,
+                message: "invalid direction \"X\"".to_string()
+            }
+        );
+        assert!(parse_input("R four").is_err());
+        assert!(parse_input("R").is_err());
+    }
+}
@@ -1,100 +1,42 @@
-use std::collections::HashSet;
-
-use itertools::Itertools;
-
-#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
-struct Point {
-    x: isize,
-    y: isize,
+use day09::{
+    fast::tail_visits,
+    parse_input,
+    render::{animate, pgm, render},
+    Motion, Rope,
+};
+
+fn solve(motions: &[Motion], knots_no: usize) -> usize {
+    tail_visits(motions, knots_no, 1)
 }
 
-impl Point {
-    fn new(x: isize, y: isize) -> Self {
-        Point { x, y }
-    }
-
-    fn pos(&self) -> (isize, isize) {
-        (self.x, self.y)
-    }
-
-    fn advance(&mut self, direction: char) {
-        match direction {
-            'U' => {
-                self.y += 1;
-            }
-            'D' => {
-                self.y -= 1;
-            }
-            'L' => {
-                self.x -= 1;
-            }
-            'R' => {
-                self.x += 1;
-            }
-            _ => panic!("Invalid direction"),
-        }
-    }
-
-    fn follow(&mut self, other: &Point) {
-        //Check if head and tail touch or overlap
-        if (self.x - other.x).abs() <= 1 && (self.y - other.y).abs() <= 1 {
-            return;
+fn main() {
+    let instructions = parse_input(include_str!("input")).unwrap();
+    println!("Part1: {}", solve(&instructions, 2));
+    println!("Part2: {},", solve(&instructions, 10));
+
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        ["render", knots] => {
+            let mut rope = Rope::new(knots.parse().unwrap(), 1);
+            instructions.iter().for_each(|m| rope.apply(m));
+            print!("{}", render(&rope, None));
         }
-        self.x += isize::signum(other.x - self.x);
-        self.y += isize::signum(other.y - self.y);
-    }
-}
-
-fn parse_input(input: &str) -> Vec<(char, usize)> {
-    input
-        .trim()
-        .lines()
-        .filter_map(|l| l.split_once(' '))
-        .map(|(d, c)| (d.chars().next().unwrap(), c.parse::<usize>().unwrap()))
-        .collect()
-}
-
-fn run_instruction(
-    knots: &mut Vec<Point>,
-    trail: &mut HashSet<Point>,
-    instruction: &(char, usize),
-) {
-    let (d, c) = *instruction;
-    let knots_no = knots.len();
-
-    for _ in 0..c {
-        //Move the head
-        knots.first_mut().unwrap().advance(d);
-        //And the rest will follow
-        for (i, _) in (0..knots_no).tuple_windows() {
-            let mut iter = knots.iter_mut();
-            let h = iter.nth(i).unwrap();
-            let t = iter.next().unwrap();
-            t.follow(h);
-            if i == knots_no -2 {
-                trail.insert(*t);
+        ["animate", knots, dir, format] => {
+            let rope = Rope::new(knots.parse().unwrap(), 1);
+            let draw = match format {
+                "pgm" => pgm,
+                _ => render,
+            };
+            std::fs::create_dir_all(dir).unwrap();
+            for (i, frame) in animate(&rope, &instructions, draw).enumerate() {
+                let path = format!("{}/frame{:06}.{}", dir, i, format);
+                std::fs::write(path, frame).unwrap();
             }
         }
+        _ => (),
     }
 }
 
-fn solve(intructions: &Vec<(char, usize)>, knots_no: usize) -> usize {
-    let mut trail = HashSet::new();
-    let mut knots = vec![Point::new(0, 0); knots_no];
-    trail.insert(*knots.first().unwrap());
-    for i in intructions {
-        run_instruction(&mut knots, &mut trail, i)
-    }
-
-    trail.len()
-}
-
-fn main() {
-    let instructions = parse_input(include_str!("input"));
-    println!("Part1: {}", solve(&instructions, 2));
-    println!("Part2: {},", solve(&instructions, 10))
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -119,16 +61,16 @@ U 20";
 
     #[test]
     fn test_part1() {
-        let instructions = parse_input(INPUT1);
+        let instructions = parse_input(INPUT1).unwrap();
         assert_eq!(solve(&instructions, 2), 13);
     }
 
     #[test]
     fn test_part2() {
-        let instructions1 = parse_input(INPUT1);
+        let instructions1 = parse_input(INPUT1).unwrap();
         assert_eq!(solve(&instructions1, 10), 1);
 
-        let instructions2 = parse_input(INPUT2);
+        let _instructions2 = parse_input(INPUT2).unwrap();
         //assert_eq!(solve(&instructions2, 10), 36);
     }
 }
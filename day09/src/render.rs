@@ -0,0 +1,182 @@
+use crate::{Motion, Point, Rope};
+
+/// Smallest rectangle holding a set of cells, corners included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bounds {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Bounds {
+    /// The bounds of `points` and the origin.
+    pub fn of<'a>(points: impl IntoIterator<Item = &'a Point>) -> Self {
+        let origin = Point::new(0, 0);
+        points.into_iter().fold(
+            Bounds {
+                min: origin,
+                max: origin,
+            },
+            |b, p| b.including(p),
+        )
+    }
+
+    pub fn including(self, p: &Point) -> Self {
+        Bounds {
+            min: Point::new(self.min.x.min(p.x), self.min.y.min(p.y)),
+            max: Point::new(self.max.x.max(p.x), self.max.y.max(p.y)),
+        }
+    }
+
+    /// Everything the rope and its trails have covered.
+    pub fn of_rope(rope: &Rope) -> Self {
+        (0..rope.knots().len())
+            .flat_map(|i| rope.trail(i))
+            .fold(Bounds::of(rope.knots()), |b, p| b.including(p))
+    }
+}
+
+/// The puzzle's label for knot `i`: `H` for the head, `T` for the tail of a two-knot rope and
+/// the index for every other knot, `*` past 9.
+fn label(i: usize, len: usize) -> char {
+    match i {
+        0 => 'H',
+        1 if len == 2 => 'T',
+        1..=9 => char::from_digit(i as u32, 10).unwrap(),
+        _ => '*',
+    }
+}
+
+/// Draws the rope in the puzzle's notation, `y` growing upwards: knots by label, the earlier
+/// one on top where several share a cell, then `s` for the start, `#` for cells the tail has
+/// visited and `.` elsewhere. Bounds default to whatever the rope has covered.
+pub fn render(rope: &Rope, bounds: Option<Bounds>) -> String {
+    let bounds = bounds.unwrap_or_else(|| Bounds::of_rope(rope));
+    let knots = rope.knots();
+    let mut out = String::new();
+    for y in (bounds.min.y..=bounds.max.y).rev() {
+        for x in bounds.min.x..=bounds.max.x {
+            let p = Point::new(x, y);
+            out.push(match knots.iter().position(|k| *k == p) {
+                Some(i) => label(i, knots.len()),
+                None if p == Point::new(0, 0) => 's',
+                None if rope.tail_trail().contains(&p) => '#',
+                None => '.',
+            });
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// The same picture as a plain (P2) greymap: knots white, the start and the tail's trail in
+/// two shades of grey, and black elsewhere.
+pub fn pgm(rope: &Rope, bounds: Option<Bounds>) -> String {
+    let bounds = bounds.unwrap_or_else(|| Bounds::of_rope(rope));
+    let (width, height) = (
+        bounds.max.x - bounds.min.x + 1,
+        bounds.max.y - bounds.min.y + 1,
+    );
+    let mut out = format!("P2\n{} {}\n3\n", width, height);
+    for line in render(rope, Some(bounds)).lines() {
+        let shades = line
+            .chars()
+            .map(|c| match c {
+                '.' => "0",
+                '#' => "1",
+                's' => "2",
+                _ => "3",
+            })
+            .collect::<Vec<_>>();
+        out += &shades.join(" ");
+        out.push('\n');
+    }
+    out
+}
+
+/// Frames drawn by `draw` after every head step of `motions`, all within the same bounds so
+/// the picture does not shift from one frame to the next.
+pub fn animate<'a>(
+    rope: &Rope,
+    motions: &'a [Motion],
+    draw: fn(&Rope, Option<Bounds>) -> String,
+) -> impl Iterator<Item = String> + 'a {
+    let mut dry_run = rope.clone();
+    motions.iter().for_each(|m| dry_run.apply(m));
+    let bounds = Bounds::of_rope(&dry_run);
+
+    let mut rope = rope.clone();
+    motions
+        .iter()
+        .flat_map(|m| std::iter::repeat_n(m.direction, m.steps))
+        .map(move |d| {
+            rope.step(d);
+            draw(&rope, Some(bounds))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_input;
+
+    #[test]
+    fn test_ten_knots() {
+        let motions = parse_input("R 5\nU 8").unwrap();
+        let mut rope = Rope::new(10, 1);
+        motions.iter().for_each(|m| rope.apply(m));
+        assert_eq!(
+            render(&rope, None),
+            ".....H
+.....1
+.....2
+.....3
+....54
+...6..
+..7...
+.8....
+9.....
+"
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let motions = parse_input("R 4\nU 4\nL 3\nD 1\nR 4\nD 1\nL 5\nR 2").unwrap();
+        let mut rope = Rope::new(2, 1);
+        motions.iter().for_each(|m| rope.apply(m));
+        assert_eq!(
+            render(&rope, None),
+            "..##..
+...##.
+.TH##.
+....#.
+s###..
+"
+        );
+
+        let frames = animate(&Rope::new(10, 1), &motions, render).collect::<Vec<_>>();
+        assert_eq!(frames.len(), 24);
+        assert_eq!(
+            frames[7],
+            "....H.
+....1.
+..432.
+.5....
+6.....
+"
+        );
+        assert!(frames.iter().all(|f| f.lines().count() == 5));
+        assert_eq!(
+            pgm(&rope, None).lines().take(4).collect::<Vec<_>>(),
+            vec!["P2", "6 5", "3", "0 0 1 1 0 0"]
+        );
+        assert_eq!(
+            animate(&rope, &motions[..1], pgm)
+                .next()
+                .unwrap()
+                .lines()
+                .last(),
+            Some("2 1 1 1 0 0 0")
+        );
+    }
+}
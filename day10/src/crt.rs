@@ -0,0 +1,253 @@
+use crate::{Cpu, Instruction, Register};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Geometry {
+    pub width: usize,
+    pub height: usize,
+    /// Pixels covered by the sprite, centred on `X`.
+    pub sprite: usize,
+}
+
+impl Default for Geometry {
+    fn default() -> Self {
+        Geometry {
+            width: 40,
+            height: 6,
+            sprite: 3,
+        }
+    }
+}
+
+impl Geometry {
+    /// Row and column drawn during `cycle`, counting from 1, ignoring the screen's height.
+    fn position(&self, cycle: usize) -> (usize, usize) {
+        ((cycle - 1) / self.width, (cycle - 1) % self.width)
+    }
+
+    /// Whether the pixel drawn during `cycle` is lit with the sprite centred on `x`.
+    pub fn lit(&self, cycle: usize, x: isize) -> bool {
+        let col = self.position(cycle).1 as isize;
+        let left = x - (self.sprite as isize - 1) / 2;
+        (left..left + self.sprite as isize).contains(&col)
+    }
+}
+
+/// What to do with a program running longer than one screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    /// Start again from the top left, drawing over the previous frame, for at most this many
+    /// frames in all, so that a program looping forever still ends.
+    Wrap(usize),
+    Error,
+}
+
+/// Frames drawn with `Overflow::Wrap` when no other limit is given.
+pub const MAX_FRAMES: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrawError {
+    /// The width, height or sprite is zero.
+    Geometry(Geometry),
+    Overflow {
+        /// The first cycle with no pixel left to draw.
+        cycle: usize,
+    },
+}
+
+impl fmt::Display for DrawError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DrawError::Geometry(g) => write!(
+                f,
+                "{}x{} screen with a {} pixel sprite: all must be positive",
+                g.width, g.height, g.sprite
+            ),
+            DrawError::Overflow { cycle } => {
+                write!(f, "cycle {} runs past the end of the screen", cycle)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DrawError {}
+
+/// The frame buffer, row by row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Screen {
+    pub width: usize,
+    pub height: usize,
+    pixels: Vec<bool>,
+}
+
+impl Screen {
+    pub fn get(&self, row: usize, col: usize) -> bool {
+        self.pixels[row * self.width + col]
+    }
+
+    /// Rows of `#` and `.`, as `ocr::read` takes them.
+    pub fn rows(&self) -> Vec<Vec<char>> {
+        self.pixels
+            .chunks(self.width.max(1))
+            .map(|row| row.iter().map(|p| if *p { '#' } else { '.' }).collect())
+            .collect()
+    }
+
+    /// A plain (P1) bitmap, lit pixels black.
+    pub fn pbm(&self) -> String {
+        let mut out = format!("P1\n{} {}\n", self.width, self.height);
+        for row in self.pixels.chunks(self.width.max(1)) {
+            let bits = row.iter().map(|p| if *p { "1" } else { "0" });
+            out += &bits.collect::<Vec<_>>().join(" ");
+            out.push('\n');
+        }
+        out
+    }
+
+    /// A black and white PNG, lit pixels white, each drawn as a `scale`×`scale` square.
+    pub fn png(&self, scale: usize) -> Vec<u8> {
+        let (width, height) = (self.width * scale, self.height * scale);
+        let data = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (y / scale, x / scale)))
+            .map(|(row, col)| if self.get(row, col) { 255 } else { 0 })
+            .collect::<Vec<u8>>();
+
+        let mut out = Vec::new();
+        let mut encoder = png::Encoder::new(&mut out, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(&data).unwrap();
+        writer.finish().unwrap();
+        out
+    }
+}
+
+impl fmt::Display for Screen {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.rows() {
+            writeln!(f, "{}", row.iter().collect::<String>())?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs `program`, drawing one pixel per cycle. Pixels never reached stay dark.
+pub fn draw(
+    program: &[Instruction],
+    geometry: &Geometry,
+    overflow: Overflow,
+) -> Result<Screen, DrawError> {
+    if geometry.width == 0 || geometry.height == 0 || geometry.sprite == 0 {
+        return Err(DrawError::Geometry(*geometry));
+    }
+    let frames = match overflow {
+        Overflow::Wrap(frames) => frames,
+        Overflow::Error => 1,
+    };
+    let mut screen = Screen {
+        width: geometry.width,
+        height: geometry.height,
+        pixels: vec![false; geometry.width * geometry.height],
+    };
+    for (cycle, r) in Cpu::new(program) {
+        let (row, col) = geometry.position(cycle);
+        if row >= geometry.height * frames {
+            return Err(DrawError::Overflow { cycle });
+        }
+        let row = row % geometry.height;
+        screen.pixels[row * geometry.width + col] = geometry.lit(cycle, r[Register::X]);
+    }
+    Ok(screen)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_input;
+
+    #[test]
+    fn test_geometry() {
+        let program = parse_input("addx 3\nnoop\naddx -2\naddx 5\nnoop").unwrap();
+        let small = Geometry {
+            width: 4,
+            height: 2,
+            sprite: 1,
+        };
+        let screen = draw(&program, &small, Overflow::Error).unwrap();
+        assert_eq!(screen.to_string(), ".#..\n..#.\n");
+
+        let wide = Geometry { sprite: 4, ..small };
+        let screen = draw(&program, &wide, Overflow::Error).unwrap();
+        assert_eq!(screen.to_string(), "##.#\n.##.\n");
+        assert_eq!(screen.pbm(), "P1\n4 2\n1 1 0 1\n0 1 1 0\n");
+    }
+
+    #[test]
+    fn test_overflow() {
+        let program = parse_input(&("noop\n".repeat(6) + "addx 1\nnoop\nnoop")).unwrap();
+        let geometry = Geometry {
+            width: 3,
+            height: 2,
+            sprite: 1,
+        };
+        assert_eq!(
+            draw(&program, &geometry, Overflow::Error),
+            Err(DrawError::Overflow { cycle: 7 })
+        );
+        let screen = draw(&program, &geometry, Overflow::Wrap(2)).unwrap();
+        assert_eq!(screen.to_string(), ".##\n.#.\n");
+
+        let looping = parse_input("addx 1\njmp -1").unwrap();
+        assert_eq!(
+            draw(&looping, &geometry, Overflow::Wrap(MAX_FRAMES)),
+            Err(DrawError::Overflow {
+                cycle: 6 * MAX_FRAMES + 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_empty_geometry() {
+        let program = parse_input("noop").unwrap();
+        for geometry in [
+            Geometry {
+                width: 0,
+                ..Geometry::default()
+            },
+            Geometry {
+                height: 0,
+                ..Geometry::default()
+            },
+            Geometry {
+                sprite: 0,
+                ..Geometry::default()
+            },
+        ] {
+            for overflow in [Overflow::Wrap(MAX_FRAMES), Overflow::Error] {
+                assert_eq!(
+                    draw(&program, &geometry, overflow),
+                    Err(DrawError::Geometry(geometry))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_png() {
+        let screen = draw(
+            &parse_input("noop").unwrap(),
+            &Geometry::default(),
+            Overflow::Error,
+        )
+        .unwrap();
+        let png = screen.png(2);
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        let decoder = png::Decoder::new(&png[..]);
+        let mut reader = decoder.read_info().unwrap();
+        let mut data = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut data).unwrap();
+        assert_eq!((reader.info().width, reader.info().height), (80, 12));
+        assert_eq!(data[..5], [255, 255, 0, 0, 0]);
+    }
+}
@@ -0,0 +1,149 @@
+use crate::{pixel, Cpu, Instruction, Register, Registers};
+use std::fmt;
+
+/// What happened during one cycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceLine {
+    pub cycle: usize,
+    pub instruction: Instruction,
+    /// Registers during the cycle.
+    pub before: Registers,
+    /// Registers once the cycle is over, changed only on an instruction's last cycle.
+    pub after: Registers,
+    pub pixel: char,
+}
+
+impl fmt::Display for TraceLine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:>5} | {:<10}",
+            self.cycle,
+            self.instruction.to_string()
+        )?;
+        for r in Register::ALL {
+            write!(f, " | {:?} {} -> {}", r, self.before[r], self.after[r])?;
+        }
+        write!(f, " | {}", self.pixel)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Breakpoint {
+    Cycle(usize),
+    /// Hit on every cycle during which the register holds the value.
+    Register(Register, isize),
+}
+
+impl Breakpoint {
+    fn hit(&self, line: &TraceLine) -> bool {
+        match *self {
+            Breakpoint::Cycle(cycle) => line.cycle == cycle,
+            Breakpoint::Register(r, value) => line.before[r] == value,
+        }
+    }
+}
+
+/// Runs a program cycle by cycle, stopping at breakpoints.
+#[derive(Debug, Clone)]
+pub struct Debugger<'a> {
+    cpu: Cpu<'a>,
+    pub breakpoints: Vec<Breakpoint>,
+}
+
+impl<'a> Debugger<'a> {
+    pub fn new(program: &'a [Instruction]) -> Self {
+        Debugger {
+            cpu: Cpu::new(program),
+            breakpoints: Vec::new(),
+        }
+    }
+
+    pub fn cpu(&self) -> &Cpu<'a> {
+        &self.cpu
+    }
+
+    /// Runs a single cycle, `None` once the program has ended.
+    pub fn step(&mut self) -> Option<TraceLine> {
+        let instruction = *self.cpu.current()?;
+        let (cycle, before) = self.cpu.tick()?;
+        Some(TraceLine {
+            cycle,
+            instruction,
+            before,
+            after: self.cpu.registers(),
+            pixel: pixel(cycle, before[Register::X]),
+        })
+    }
+
+    /// Runs until the end of a cycle hitting a breakpoint, passing every cycle to `trace` on
+    /// the way. Returns the breakpoint and the cycle it stopped on, or `None` if the program
+    /// ended first.
+    pub fn resume(&mut self, mut trace: impl FnMut(&TraceLine)) -> Option<(Breakpoint, TraceLine)> {
+        while let Some(line) = self.step() {
+            trace(&line);
+            if let Some(bp) = self.breakpoints.iter().find(|bp| bp.hit(&line)) {
+                return Some((*bp, line));
+            }
+        }
+        None
+    }
+}
+
+/// Every cycle the program runs for, as a trace.
+impl Iterator for Debugger<'_> {
+    type Item = TraceLine;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.step()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_input;
+
+    #[test]
+    fn test_trace() {
+        let program = parse_input("noop\naddx 3\naddx -5").unwrap();
+        let trace = Debugger::new(&program)
+            .map(|l| l.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            trace,
+            vec![
+                "    1 | noop       | X 1 -> 1 | Y 0 -> 0 | #",
+                "    2 | addx 3     | X 1 -> 1 | Y 0 -> 0 | #",
+                "    3 | addx 3     | X 1 -> 4 | Y 0 -> 0 | #",
+                "    4 | addx -5    | X 4 -> 4 | Y 0 -> 0 | #",
+                "    5 | addx -5    | X 4 -> -1 | Y 0 -> 0 | #",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        let program = parse_input("addx 2\naddx 3\nnoop\naddx 1\nnoop").unwrap();
+        let mut debugger = Debugger::new(&program);
+        debugger.breakpoints = vec![Breakpoint::Register(Register::X, 6), Breakpoint::Cycle(2)];
+
+        let mut seen = Vec::new();
+        let (bp, line) = debugger.resume(|l| seen.push(l.cycle)).unwrap();
+        assert_eq!(
+            (bp, line.cycle, seen),
+            (Breakpoint::Cycle(2), 2, vec![1, 2])
+        );
+
+        let (bp, line) = debugger.resume(|_| ()).unwrap();
+        assert_eq!((bp, line.cycle), (Breakpoint::Register(Register::X, 6), 5));
+        assert_eq!(line.instruction.to_string(), "noop");
+
+        let cycles = std::iter::from_fn(|| debugger.resume(|_| ()))
+            .map(|(_, l)| l.cycle)
+            .collect::<Vec<_>>();
+        assert_eq!(cycles, vec![6, 7]);
+        assert_eq!(debugger.resume(|_| ()), None);
+        assert_eq!(debugger.cpu().cycle(), 8);
+    }
+}
@@ -0,0 +1,314 @@
+pub mod crt;
+pub mod debug;
+pub mod ocr;
+
+use std::{fmt, ops::Index};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Register {
+    X,
+    Y,
+}
+
+impl Register {
+    pub const ALL: [Register; 2] = [Register::X, Register::Y];
+}
+
+/// Every register's value, `X` starting at 1 and the others at 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Registers([isize; Register::ALL.len()]);
+
+impl Default for Registers {
+    fn default() -> Self {
+        let mut values = [0; Register::ALL.len()];
+        values[Register::X as usize] = 1;
+        Registers(values)
+    }
+}
+
+impl Index<Register> for Registers {
+    type Output = isize;
+
+    fn index(&self, r: Register) -> &isize {
+        &self.0[r as usize]
+    }
+}
+
+impl Registers {
+    pub fn set(&mut self, r: Register, value: isize) {
+        self.0[r as usize] = value;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Noop,
+    Add(Register),
+    Mul(Register),
+    /// Moves the program counter by the argument, relative to the jump itself.
+    Jump,
+}
+
+struct Spec {
+    mnemonic: &'static str,
+    op: Op,
+    cycles: usize,
+}
+
+/// The instruction set: how each instruction is written and how many cycles it takes.
+/// The result of an instruction is visible from the cycle after its last one.
+const INSTRUCTION_SET: &[Spec] = &[
+    Spec {
+        mnemonic: "noop",
+        op: Op::Noop,
+        cycles: 1,
+    },
+    Spec {
+        mnemonic: "addx",
+        op: Op::Add(Register::X),
+        cycles: 2,
+    },
+    Spec {
+        mnemonic: "addy",
+        op: Op::Add(Register::Y),
+        cycles: 2,
+    },
+    Spec {
+        mnemonic: "mulx",
+        op: Op::Mul(Register::X),
+        cycles: 3,
+    },
+    Spec {
+        mnemonic: "muly",
+        op: Op::Mul(Register::Y),
+        cycles: 3,
+    },
+    Spec {
+        mnemonic: "jmp",
+        op: Op::Jump,
+        cycles: 2,
+    },
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instruction {
+    pub op: Op,
+    pub arg: isize,
+}
+
+impl Instruction {
+    fn spec(&self) -> &'static Spec {
+        INSTRUCTION_SET.iter().find(|s| s.op == self.op).unwrap()
+    }
+
+    pub fn cycles(&self) -> usize {
+        self.spec().cycles
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.op {
+            Op::Noop => write!(f, "{}", self.spec().mnemonic),
+            _ => write!(f, "{} {}", self.spec().mnemonic, self.arg),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+pub fn parse_input(input: &str) -> Result<Vec<Instruction>, ParseError> {
+    input
+        .trim()
+        .lines()
+        .enumerate()
+        .map(|(i, l)| {
+            let err = |message: String| ParseError {
+                line: i + 1,
+                message,
+            };
+            let mut tokens = l.split_ascii_whitespace();
+            let mnemonic = tokens.next().unwrap_or_default();
+            let spec = INSTRUCTION_SET
+                .iter()
+                .find(|s| s.mnemonic == mnemonic)
+                .ok_or_else(|| err(format!("unknown instruction {:?}", mnemonic)))?;
+            let arg = match (spec.op, tokens.next()) {
+                (Op::Noop, None) => 0,
+                (Op::Noop, Some(_)) => return Err(err("noop takes no argument".to_string())),
+                (_, Some(arg)) => arg
+                    .parse()
+                    .map_err(|_| err(format!("invalid argument {:?}", arg)))?,
+                (_, None) => return Err(err(format!("{} needs an argument", mnemonic))),
+            };
+            match tokens.next() {
+                Some(extra) => Err(err(format!("unexpected {:?}", extra))),
+                None => Ok(Instruction { op: spec.op, arg }),
+            }
+        })
+        .collect()
+}
+
+/// Runs a program one clock cycle at a time. The program stops when the program counter
+/// leaves it.
+#[derive(Debug, Clone)]
+pub struct Cpu<'a> {
+    program: &'a [Instruction],
+    pc: Option<usize>,
+    registers: Registers,
+    cycle: usize,
+    /// Cycles already spent on the instruction at `pc`.
+    busy: usize,
+}
+
+impl<'a> Cpu<'a> {
+    pub fn new(program: &'a [Instruction]) -> Self {
+        Cpu {
+            program,
+            pc: Some(0),
+            registers: Registers::default(),
+            cycle: 0,
+            busy: 0,
+        }
+    }
+
+    /// Cycles run so far.
+    pub fn cycle(&self) -> usize {
+        self.cycle
+    }
+
+    pub fn registers(&self) -> Registers {
+        self.registers
+    }
+
+    /// The instruction being executed, or about to be.
+    pub fn current(&self) -> Option<&'a Instruction> {
+        self.pc.and_then(|pc| self.program.get(pc))
+    }
+
+    fn execute(&mut self, instruction: &Instruction, pc: usize) {
+        let r = &mut self.registers;
+        self.pc = match instruction.op {
+            Op::Noop => Some(pc + 1),
+            Op::Add(reg) => {
+                r.set(reg, r[reg] + instruction.arg);
+                Some(pc + 1)
+            }
+            Op::Mul(reg) => {
+                r.set(reg, r[reg] * instruction.arg);
+                Some(pc + 1)
+            }
+            Op::Jump => pc.checked_add_signed(instruction.arg),
+        };
+    }
+
+    /// Runs one cycle, returning its number and the registers during it.
+    pub fn tick(&mut self) -> Option<(usize, Registers)> {
+        let instruction = self.current()?;
+        let during = self.registers;
+        self.cycle += 1;
+        self.busy += 1;
+        if self.busy == instruction.cycles() {
+            self.busy = 0;
+            self.execute(instruction, self.pc.unwrap());
+        }
+        Some((self.cycle, during))
+    }
+}
+
+/// The cycles `part1` samples the signal at.
+pub const INTERESTING_CYCLES: [usize; 6] = [20, 60, 100, 140, 180, 220];
+
+/// Sum of the cycle number times `X` over the given cycles. Stops after the last of them,
+/// so a program that loops forever still gives an answer.
+pub fn signal_strength(program: &[Instruction], cycles: &[usize]) -> isize {
+    let last = cycles.iter().copied().max().unwrap_or(0);
+    Cpu::new(program)
+        .take_while(|(cycle, _)| *cycle <= last)
+        .filter(|(cycle, _)| cycles.contains(cycle))
+        .map(|(cycle, r)| cycle as isize * r[Register::X])
+        .sum()
+}
+
+/// What the puzzle's CRT draws during `cycle` when the sprite is centred on `x`.
+pub fn pixel(cycle: usize, x: isize) -> char {
+    match crt::Geometry::default().lit(cycle, x) {
+        true => '#',
+        false => '.',
+    }
+}
+
+/// Every cycle the program runs for, numbered from 1, with the registers during that cycle.
+impl Iterator for Cpu<'_> {
+    type Item = (usize, Registers);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.tick()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn x_values(program: &str) -> Vec<isize> {
+        let program = parse_input(program).unwrap();
+        Cpu::new(&program).map(|(_, r)| r[Register::X]).collect()
+    }
+
+    #[test]
+    fn test_timing() {
+        assert_eq!(x_values("noop\naddx 3\naddx -5\nnoop"), [1, 1, 1, 4, 4, -1]);
+        assert_eq!(x_values("mulx 5\naddx 1\nnoop"), [1, 1, 1, 5, 5, 6]);
+    }
+
+    #[test]
+    fn test_extensions() {
+        let program = parse_input("addy 2\nmuly 4\njmp 2\naddx 100\naddx 1").unwrap();
+        let cycles = Cpu::new(&program).collect::<Vec<_>>();
+        assert_eq!(cycles.len(), 2 + 3 + 2 + 2);
+        let (cycle, last) = cycles[cycles.len() - 1];
+        assert_eq!(cycle, 9);
+        assert_eq!(last[Register::Y], 8);
+        assert_eq!(last[Register::X], 1);
+
+        let looping = parse_input("addx 1\njmp -1").unwrap();
+        let x = Cpu::new(&looping).nth(99).map(|(_, r)| r[Register::X]);
+        assert_eq!(x, Some(26));
+        assert_eq!(Cpu::new(&parse_input("jmp -5").unwrap()).count(), 2);
+    }
+
+    #[test]
+    fn test_signal_strength_looping() {
+        let looping = parse_input("addx 1\njmp -1").unwrap();
+        assert_eq!(signal_strength(&looping, &[20]), 20 * 6);
+        assert_eq!(signal_strength(&looping, &[]), 0);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse_input("noop\nsubx 3").unwrap_err(),
+            ParseError {
+                line: 2,
+                message: "unknown instruction \"subx\"".to_string()
+            }
+        );
+        assert!(parse_input("addx").is_err());
+        assert!(parse_input("addx one").is_err());
+        assert!(parse_input("noop 1").is_err());
+        assert!(parse_input("addx 1 2").is_err());
+    }
+}
@@ -1,104 +1,89 @@
-#[derive(Debug)]
-enum OP {
-    Noop,
-    Addx,
-}
+use day10::{
+    crt::{draw, Geometry, Overflow, MAX_FRAMES},
+    debug::{Breakpoint, Debugger},
+    ocr, parse_input, signal_strength, Instruction, Register, INTERESTING_CYCLES,
+};
 
-fn part1(instructions: &Vec<(OP, isize)>) -> isize {
-    let ckeck_cycle = |cycle: isize, x: isize| -> isize {
-        match cycle {
-            20 | 60 | 100 | 140 | 180 | 220 => cycle * x,
-            _ => 0,
-        }
-    };
+fn part1(instructions: &[Instruction]) -> isize {
+    signal_strength(instructions, &INTERESTING_CYCLES)
+}
 
-    let mut cycle = 0_isize;
-    let mut x = 1;
-    let mut strength = 0;
+fn part2(instructions: &[Instruction]) -> Vec<Vec<char>> {
+    draw(instructions, &Geometry::default(), Overflow::Error)
+        .unwrap()
+        .rows()
+}
 
-    for (op, n) in instructions {
-        match op {
-            OP::Noop => {
-                cycle += 1;
-                strength += ckeck_cycle(cycle, x);
-            }
-            OP::Addx => {
-                cycle += 1;
-                strength += ckeck_cycle(cycle, x);
-                cycle += 1;
-                strength += ckeck_cycle(cycle, x);
-                x += n;
-            }
+fn main() {
+    let input = include_str!("input");
+    println!("Part1: {}\n", part1(&parse_input(input).unwrap()));
+    let crt = part2(&parse_input(input).unwrap());
+    match ocr::read(&crt) {
+        Ok(letters) => println!("Part2: {}", letters),
+        Err(e) => {
+            eprintln!("{}", e);
+            println!("Part2: \n");
+            let text = crt
+                .iter()
+                .map(String::from_iter)
+                .collect::<Vec<String>>()
+                .join("\n");
+            println!("{}", text);
         }
     }
-    strength
-}
 
-fn part2(instructions: &Vec<(OP, isize)>) -> Vec<Vec<char>> {
-    let get_pixel = |pos: usize, x: isize| -> char {
-        match ((x - 1)..=(x + 1)).contains(&(pos as isize)) {
-            true => '#',
-            false => '.',
+    let program = parse_input(input).unwrap();
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        ["trace"] => Debugger::new(&program).for_each(|l| println!("{}", l)),
+        ["strength", ref cycles @ ..] => {
+            let cycles = cycles
+                .iter()
+                .map(|c| c.parse().unwrap())
+                .collect::<Vec<_>>();
+            println!("{}", signal_strength(&program, &cycles));
         }
-    };
-    let crt_row = |cycle: usize| -> usize { ((cycle - 1) / 40) as usize };
-    let crt_col = |cycle: usize| -> usize { ((cycle - 1) % 40) as usize };
-
-    let mut crt: Vec<Vec<char>> = vec![vec![]; 6];
-    let mut cycle = 0_usize;
-    let mut x = 1_isize;
-
-    for (op, n) in instructions {
-        match op {
-            OP::Noop => {
-                cycle += 1;
-                crt.get_mut(crt_row(cycle))
-                    .unwrap()
-                    .push(get_pixel(crt_col(cycle), x));
-            }
-
-            OP::Addx => {
-                cycle += 1;
-                crt.get_mut(crt_row(cycle))
-                    .unwrap()
-                    .push(get_pixel(crt_col(cycle), x));
-
-                cycle += 1;
-                crt.get_mut(crt_row(cycle))
-                    .unwrap()
-                    .push(get_pixel(crt_col(cycle), x));
-                x += n;
+        ["debug", ref breakpoints @ ..] => {
+            let mut debugger = Debugger::new(&program);
+            debugger.breakpoints = breakpoints
+                .iter()
+                .map(|bp| match bp.split_once('=') {
+                    Some(("x", v)) => Breakpoint::Register(Register::X, v.parse().unwrap()),
+                    Some(("y", v)) => Breakpoint::Register(Register::Y, v.parse().unwrap()),
+                    _ => Breakpoint::Cycle(bp.parse().unwrap()),
+                })
+                .collect();
+            while let Some((bp, _)) = debugger.resume(|l| println!("{}", l)) {
+                println!("== {:?} ==", bp);
             }
         }
+        ["screen", width, height, sprite, overflow, path] => {
+            let geometry = Geometry {
+                width: width.parse().unwrap(),
+                height: height.parse().unwrap(),
+                sprite: sprite.parse().unwrap(),
+            };
+            let overflow = match overflow.split_once('=') {
+                Some(("wrap", frames)) => Overflow::Wrap(frames.parse().unwrap()),
+                None if overflow == "wrap" => Overflow::Wrap(MAX_FRAMES),
+                _ => Overflow::Error,
+            };
+            let screen = match draw(&program, &geometry, overflow) {
+                Ok(screen) => screen,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            };
+            let data = match path.rsplit_once('.').map(|(_, ext)| ext) {
+                Some("png") => screen.png(8),
+                Some("pbm") => screen.pbm().into_bytes(),
+                _ => screen.to_string().into_bytes(),
+            };
+            std::fs::write(path, data).unwrap();
+        }
+        _ => (),
     }
-    crt
-}
-
-fn parse_input(input: &str) -> Vec<(OP, isize)> {
-    input
-        .trim()
-        .lines()
-        .map(|l| match l.starts_with("noop") {
-            true => (OP::Noop, 0),
-            false => (
-                OP::Addx,
-                l.strip_prefix("addx ").unwrap().parse::<isize>().unwrap(),
-            ),
-        })
-        .collect()
-}
-
-fn main() {
-    let input = include_str!("input");
-    println!("Part1: {}\n", part1(&parse_input(input)));
-    println!("Part2: \n");
-    let crt = part2(&parse_input(input));
-    let text = crt
-        .iter()
-        .map(|c| String::from_iter(c))
-        .collect::<Vec<String>>()
-        .join("\n");
-    println!("{}", text);
 }
 
 #[cfg(test)]
@@ -254,17 +239,22 @@ noop";
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1(&parse_input(INPUT)), 13140);
+        assert_eq!(part1(&parse_input(INPUT).unwrap()), 13140);
     }
 
     #[test]
     fn test_part2() {
-        let crt = part2(&parse_input(INPUT));
-        let text = crt
-            .iter()
-            .map(|c| String::from_iter(c))
-            .collect::<Vec<String>>()
-            .join("\n");
-        println!("{}", text);
+        let crt = part2(&parse_input(INPUT).unwrap());
+        assert_eq!(
+            crt[0].iter().collect::<String>(),
+            "##..##..##..##..##..##..##..##..##..##.."
+        );
+        assert_eq!(
+            ocr::read(&crt),
+            Err(ocr::OcrError::Unrecognised((0..8).collect()))
+        );
+
+        let crt = part2(&parse_input(include_str!("input")).unwrap());
+        assert_eq!(ocr::read(&crt), Ok("RGLRBZAU".to_string()));
     }
 }
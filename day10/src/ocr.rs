@@ -0,0 +1,135 @@
+use std::fmt;
+
+pub const GLYPH_WIDTH: usize = 4;
+pub const GLYPH_HEIGHT: usize = 6;
+/// Columns from the start of one letter to the start of the next.
+const PITCH: usize = GLYPH_WIDTH + 1;
+
+/// The letters of the 4×6 puzzle font, each as its rows of pixels.
+const FONT: &[(char, [&str; GLYPH_HEIGHT])] = &[
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', [".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OcrError {
+    /// The screen is not one glyph tall.
+    Height(usize),
+    /// Positions of the glyphs matching no letter, counting from 0 on the left.
+    Unrecognised(Vec<usize>),
+}
+
+impl fmt::Display for OcrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OcrError::Height(rows) => write!(
+                f,
+                "screen is {} rows tall instead of {}",
+                rows, GLYPH_HEIGHT
+            ),
+            OcrError::Unrecognised(positions) => {
+                write!(f, "unrecognised glyphs at positions {:?}", positions)
+            }
+        }
+    }
+}
+
+impl std::error::Error for OcrError {}
+
+/// The letter drawn in columns `start..start + GLYPH_WIDTH`, `#` being a lit pixel.
+fn glyph(screen: &[Vec<char>], start: usize) -> Option<char> {
+    let lit = |row: &Vec<char>, c: usize| row.get(c) == Some(&'#');
+    FONT.iter()
+        .find(|(_, rows)| {
+            screen.iter().zip(rows).all(|(row, pattern)| {
+                pattern
+                    .bytes()
+                    .enumerate()
+                    .all(|(i, p)| lit(row, start + i) == (p == b'#'))
+            })
+        })
+        .map(|(letter, _)| *letter)
+}
+
+/// Reads the letters off a screen of `#` and `.`, one glyph every 5 columns.
+pub fn read(screen: &[Vec<char>]) -> Result<String, OcrError> {
+    if screen.len() != GLYPH_HEIGHT {
+        return Err(OcrError::Height(screen.len()));
+    }
+    let width = screen.iter().map(Vec::len).max().unwrap_or(0);
+    let glyphs = (0..width.div_ceil(PITCH))
+        .map(|i| glyph(screen, i * PITCH))
+        .collect::<Vec<_>>();
+    let unrecognised = glyphs
+        .iter()
+        .enumerate()
+        .filter(|(_, g)| g.is_none())
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+    match unrecognised.is_empty() {
+        true => Ok(glyphs.into_iter().flatten().collect()),
+        false => Err(OcrError::Unrecognised(unrecognised)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screen(text: &str) -> Vec<Vec<char>> {
+        text.lines().map(|l| l.chars().collect()).collect()
+    }
+
+    #[test]
+    fn test_read() {
+        let hello = screen(
+            "#..#.####.#....#.....##.
+#..#.#....#....#....#..#
+####.###..#....#....#..#
+#..#.#....#....#....#..#
+#..#.#....#....#....#..#
+#..#.####.####.####..##.",
+        );
+        assert_eq!(read(&hello), Ok("HELLO".to_string()));
+        let font = FONT
+            .iter()
+            .map(|(_, rows)| rows.map(|r| r.to_string() + "."))
+            .fold(vec![String::new(); GLYPH_HEIGHT], |mut acc, rows| {
+                acc.iter_mut().zip(rows).for_each(|(a, r)| *a += &r);
+                acc
+            });
+        let font = font.iter().map(|l| l.chars().collect()).collect::<Vec<_>>();
+        assert_eq!(read(&font), Ok("ABCEFGHIJKLOPRSUZ".to_string()));
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut garbled = screen(
+            "####.####.####
+#....#....#...
+###..###..###.
+#....#....#...
+#....#....#...
+####.####.####",
+        );
+        garbled[1][5] = '.';
+        garbled[3][11] = '#';
+        assert_eq!(read(&garbled), Err(OcrError::Unrecognised(vec![1, 2])));
+        assert_eq!(read(&garbled[..5]), Err(OcrError::Height(5)));
+    }
+}
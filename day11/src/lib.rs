@@ -0,0 +1,539 @@
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete as ch,
+    combinator::{all_consuming, map, value},
+    multi::{fold_many0, separated_list0},
+    sequence::{delimited, pair, preceded, tuple},
+    Finish, IResult,
+};
+use std::{cmp::Ordering, fmt};
+
+/// Arithmetic on the old worry level.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    Old,
+    Num(usize),
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    Div(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    /// `None` on overflow, on a negative result and on division by zero.
+    pub fn eval(&self, old: usize) -> Option<usize> {
+        match self {
+            Expr::Old => Some(old),
+            Expr::Num(n) => Some(*n),
+            Expr::Add(a, b) => a.eval(old)?.checked_add(b.eval(old)?),
+            Expr::Sub(a, b) => a.eval(old)?.checked_sub(b.eval(old)?),
+            Expr::Mul(a, b) => a.eval(old)?.checked_mul(b.eval(old)?),
+            Expr::Div(a, b) => a.eval(old)?.checked_div(b.eval(old)?),
+        }
+    }
+
+    /// Whether the expression only adds and multiplies, so that it can be worked out on
+    /// remainders alone.
+    pub fn is_polynomial(&self) -> bool {
+        match self {
+            Expr::Old | Expr::Num(_) => true,
+            Expr::Add(a, b) | Expr::Mul(a, b) => a.is_polynomial() && b.is_polynomial(),
+            Expr::Sub(..) | Expr::Div(..) => false,
+        }
+    }
+
+    /// The result modulo `m`, reducing after every step so that nothing overflows. `None` if
+    /// the expression is not a polynomial.
+    pub fn eval_mod(&self, old: usize, m: usize) -> Option<usize> {
+        let m = m as u128;
+        let reduce = |a: &Expr, b: &Expr, f: fn(u128, u128) -> u128| {
+            let (a, b) = (a.eval_mod(old, m as usize)?, b.eval_mod(old, m as usize)?);
+            Some((f(a as u128, b as u128) % m) as usize)
+        };
+        match self {
+            Expr::Old => Some((old as u128 % m) as usize),
+            Expr::Num(n) => Some((*n as u128 % m) as usize),
+            Expr::Add(a, b) => reduce(a, b, |a, b| a + b),
+            Expr::Mul(a, b) => reduce(a, b, |a, b| a * b),
+            Expr::Sub(..) | Expr::Div(..) => None,
+        }
+    }
+
+    /// `*` and `/` bind tighter than `+` and `-`, all of them to the left, and parentheses
+    /// group.
+    fn parse(input: &str) -> IResult<&str, Expr> {
+        fn factor(input: &str) -> IResult<&str, Expr> {
+            delimited(
+                ch::space0,
+                alt((
+                    value(Expr::Old, tag("old")),
+                    map(number, Expr::Num),
+                    delimited(ch::char('('), Expr::parse, ch::char(')')),
+                )),
+                ch::space0,
+            )(input)
+        }
+
+        fn chain<'a>(
+            operand: fn(&'a str) -> IResult<&'a str, Expr>,
+            ops: (char, char),
+            build: fn(char, Expr, Expr) -> Expr,
+        ) -> impl FnMut(&'a str) -> IResult<&'a str, Expr> {
+            move |input| {
+                let (input, first) = operand(input)?;
+                fold_many0(
+                    pair(alt((ch::char(ops.0), ch::char(ops.1))), operand),
+                    move || first.clone(),
+                    move |acc, (op, rhs)| build(op, acc, rhs),
+                )(input)
+            }
+        }
+
+        fn term(input: &str) -> IResult<&str, Expr> {
+            chain(factor, ('*', '/'), |op, a, b| match op {
+                '*' => Expr::Mul(Box::new(a), Box::new(b)),
+                _ => Expr::Div(Box::new(a), Box::new(b)),
+            })(input)
+        }
+
+        chain(term, ('+', '-'), |op, a, b| match op {
+            '+' => Expr::Add(Box::new(a), Box::new(b)),
+            _ => Expr::Sub(Box::new(a), Box::new(b)),
+        })(input)
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expr::Old => write!(f, "old"),
+            Expr::Num(n) => write!(f, "{}", n),
+            Expr::Add(a, b) => write!(f, "({} + {})", a, b),
+            Expr::Sub(a, b) => write!(f, "({} - {})", a, b),
+            Expr::Mul(a, b) => write!(f, "({} * {})", a, b),
+            Expr::Div(a, b) => write!(f, "({} / {})", a, b),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Monkey {
+    pub id: usize,
+    pub items: Vec<usize>,
+    pub operation: Expr,
+    pub divisible_by: usize,
+    /// Where items go when the test passes, and when it fails.
+    pub next: (usize, usize),
+    pub inspection: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// The id of the monkey being described, once its header has been read.
+    pub monkey: Option<usize>,
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(id) = self.monkey {
+            write!(f, "monkey {}, ", id)?;
+        }
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Label {
+    Monkey,
+    Items,
+    Operation,
+    Test,
+    IfTrue,
+    IfFalse,
+}
+
+impl fmt::Display for Label {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Label::Monkey => "monkey header",
+            Label::Items => "starting items",
+            Label::Operation => "operation",
+            Label::Test => "test",
+            Label::IfTrue => "if true",
+            Label::IfFalse => "if false",
+        })
+    }
+}
+
+enum Field {
+    Monkey(usize),
+    Items(Vec<usize>),
+    Operation(Expr),
+    Test(usize),
+    IfTrue(usize),
+    IfFalse(usize),
+}
+
+fn number(input: &str) -> IResult<&str, usize> {
+    map(ch::u64, |n| n as usize)(input)
+}
+
+/// The words starting a line, which tell what the rest of it should be.
+fn label(input: &str) -> IResult<&str, Label> {
+    let words = |first, second| tuple((tag(first), ch::space1, tag(second)));
+    preceded(
+        ch::space0,
+        alt((
+            value(Label::Monkey, tag("Monkey")),
+            value(
+                Label::Items,
+                pair(words("Starting", "items"), ch::char(':')),
+            ),
+            value(Label::Operation, tag("Operation:")),
+            value(Label::Test, tag("Test:")),
+            value(Label::IfTrue, pair(words("If", "true"), ch::char(':'))),
+            value(Label::IfFalse, pair(words("If", "false"), ch::char(':'))),
+        )),
+    )(input)
+}
+
+fn body(label: Label, input: &str) -> IResult<&str, Field> {
+    let throw = || {
+        preceded(
+            tuple((
+                ch::space0,
+                tag("throw"),
+                ch::space1,
+                tag("to"),
+                ch::space1,
+                tag("monkey"),
+                ch::space1,
+            )),
+            number,
+        )
+    };
+    let (rest, field) = match label {
+        Label::Monkey => map(
+            delimited(ch::space1, number, pair(ch::space0, ch::char(':'))),
+            Field::Monkey,
+        )(input),
+        Label::Items => map(
+            separated_list0(ch::char(','), delimited(ch::space0, number, ch::space0)),
+            Field::Items,
+        )(input),
+        Label::Operation => map(
+            preceded(
+                tuple((ch::space0, tag("new"), ch::space0, ch::char('='))),
+                Expr::parse,
+            ),
+            Field::Operation,
+        )(input),
+        Label::Test => map(
+            preceded(
+                tuple((
+                    ch::space0,
+                    tag("divisible"),
+                    ch::space1,
+                    tag("by"),
+                    ch::space1,
+                )),
+                number,
+            ),
+            Field::Test,
+        )(input),
+        Label::IfTrue => map(throw(), Field::IfTrue)(input),
+        Label::IfFalse => map(throw(), Field::IfFalse)(input),
+    }?;
+    let (rest, _) = all_consuming(ch::space0)(rest)?;
+    Ok((rest, field))
+}
+
+/// A monkey's header and fields, with the line each came from.
+type Block = Vec<(usize, Field)>;
+
+fn parse_line(line: usize, text: &str, monkey: Option<usize>) -> Result<Field, ParseError> {
+    let err = |message: String| ParseError {
+        monkey,
+        line,
+        message,
+    };
+    let (rest, label) = label(text)
+        .finish()
+        .map_err(|_| err(format!("unrecognised line {:?}", text.trim())))?;
+    body(label, rest)
+        .finish()
+        .map(|(_, field)| field)
+        .map_err(|_| err(format!("invalid {} {:?}", label, text.trim())))
+}
+
+fn build(block: Block) -> Result<Monkey, ParseError> {
+    let (header_line, id) = match block[0] {
+        (line, Field::Monkey(id)) => (line, id),
+        (line, _) => {
+            return Err(ParseError {
+                monkey: None,
+                line,
+                message: "expected a \"Monkey <id>:\" header".to_string(),
+            })
+        }
+    };
+    let err = |line, message: String| ParseError {
+        monkey: Some(id),
+        line,
+        message,
+    };
+
+    let (mut items, mut operation, mut test, mut if_true, mut if_false) =
+        (None, None, None, None, None);
+    for (line, field) in block.into_iter().skip(1) {
+        let duplicate = match field {
+            Field::Monkey(_) => Some("a second header"),
+            Field::Items(v) => items.replace(v).map(|_| "starting items"),
+            Field::Operation(e) => operation.replace(e).map(|_| "operation"),
+            Field::Test(d) => test.replace(d).map(|_| "test"),
+            Field::IfTrue(m) => if_true.replace(m).map(|_| "if true"),
+            Field::IfFalse(m) => if_false.replace(m).map(|_| "if false"),
+        };
+        if let Some(what) = duplicate {
+            return Err(err(line, format!("{} given twice", what)));
+        }
+    }
+    let missing = |what: &str| err(header_line, format!("no {}", what));
+    let divisible_by = test.ok_or_else(|| missing("test"))?;
+    if divisible_by == 0 {
+        return Err(err(header_line, "divisible by 0".to_string()));
+    }
+
+    Ok(Monkey {
+        id,
+        items: items.ok_or_else(|| missing("starting items"))?,
+        operation: operation.ok_or_else(|| missing("operation"))?,
+        divisible_by,
+        next: (
+            if_true.ok_or_else(|| missing("if true"))?,
+            if_false.ok_or_else(|| missing("if false"))?,
+        ),
+        inspection: 0,
+    })
+}
+
+/// Least common multiple, `None` if it does not fit in a `usize`.
+pub fn lcm(a: usize, b: usize) -> Option<usize> {
+    fn gcd(a: usize, b: usize) -> usize {
+        match b {
+            0 => a,
+            _ => gcd(b, a % b),
+        }
+    }
+    (a / gcd(a, b)).checked_mul(b)
+}
+
+/// Monkeys sorted by id. Fields may come in any order within a monkey, and ids must run
+/// from 0 without gaps, but not necessarily in order. Worry levels must be kept modulo a
+/// common multiple of the divisors, so that has to fit in a `usize`.
+pub fn parse_input(input: &str) -> Result<Vec<Monkey>, ParseError> {
+    let mut blocks: Vec<Block> = Vec::new();
+    let mut in_block = false;
+    for (i, text) in input.lines().enumerate() {
+        if text.trim().is_empty() {
+            in_block = false;
+            continue;
+        }
+        let monkey = match blocks.last() {
+            Some(block) if in_block => match block[0].1 {
+                Field::Monkey(id) => Some(id),
+                _ => None,
+            },
+            _ => None,
+        };
+        let field = parse_line(i + 1, text, monkey)?;
+        if !in_block || matches!(field, Field::Monkey(_)) {
+            blocks.push(Vec::new());
+        }
+        in_block = true;
+        blocks.last_mut().unwrap().push((i + 1, field));
+    }
+
+    let lines = blocks
+        .iter()
+        .map(|b| {
+            let line = |f: fn(&Field) -> bool| b.iter().find(|(_, x)| f(x)).map(|(l, _)| *l);
+            (
+                b[0].0,
+                line(|f| matches!(f, Field::IfTrue(_))),
+                line(|f| matches!(f, Field::IfFalse(_))),
+            )
+        })
+        .collect::<Vec<_>>();
+    let mut monkeys = blocks
+        .into_iter()
+        .map(build)
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut order = (0..monkeys.len()).collect::<Vec<_>>();
+    order.sort_by_key(|i| monkeys[*i].id);
+    let mut modulus = 1;
+    for (expected, i) in order.iter().enumerate() {
+        let m = &monkeys[*i];
+        let (header, if_true, if_false) = lines[*i];
+        let err = |line: Option<usize>, message: String| ParseError {
+            monkey: Some(m.id),
+            line: line.unwrap_or(header),
+            message,
+        };
+        match m.id.cmp(&expected) {
+            Ordering::Less => return Err(err(None, "id given twice".to_string())),
+            Ordering::Greater => return Err(err(None, format!("no monkey {}", expected))),
+            Ordering::Equal => (),
+        }
+        modulus = lcm(modulus, m.divisible_by).ok_or_else(|| {
+            err(
+                None,
+                "divisors have no common multiple that fits".to_string(),
+            )
+        })?;
+        for (target, line) in [(m.next.0, if_true), (m.next.1, if_false)] {
+            if target >= monkeys.len() || target == m.id {
+                return Err(err(line, format!("cannot throw to monkey {}", target)));
+            }
+        }
+    }
+    monkeys.sort_by_key(|m| m.id);
+    Ok(monkeys)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expr(text: &str) -> Expr {
+        all_consuming(Expr::parse)(text).finish().unwrap().1
+    }
+
+    #[test]
+    fn test_expressions() {
+        assert_eq!(expr("old + old").eval(7), Some(14));
+        assert_eq!(expr("old * 3 + 2").eval(5), Some(17));
+        assert_eq!(expr("2 + old * 3").eval(5), Some(17));
+        assert_eq!(expr(" (old+1)*(old - 1) ").eval(5), Some(24));
+        assert_eq!(expr("old - 3 - 1").eval(10), Some(6));
+        assert_eq!(expr("old / 2 / 2").eval(17), Some(4));
+        assert_eq!(expr("old - 3").eval(2), None);
+        assert_eq!(expr("old / (old - old)").eval(2), None);
+        assert_eq!(expr("old * 3 + 2").to_string(), "((old * 3) + 2)");
+    }
+
+    #[test]
+    fn test_modular_expressions() {
+        let cube = expr("old * old * old + 7");
+        assert_eq!(cube.eval(6_000_000), None);
+        let m = 2 * 3 * 5 * 7 * 11 * 13 * 17 * 19 * 23;
+        let exact = (6_000_000u128.pow(3) + 7) % m as u128;
+        assert_eq!(cube.eval_mod(6_000_000, m), Some(exact as usize));
+        assert_eq!(cube.eval_mod(usize::MAX, usize::MAX - 1), Some(8));
+        assert!(cube.is_polynomial());
+
+        assert!(!expr("old * 2 - 1").is_polynomial());
+        assert_eq!(expr("(old / 2) * 3").eval_mod(4, 5), None);
+    }
+
+    #[test]
+    fn test_layout() {
+        let monkeys = parse_input(
+            "Monkey 1:
+  Operation: new = old*old
+  Starting   items:79,  60
+  If false: throw to monkey 0
+  Test: divisible by 13
+  If true:  throw  to monkey   0
+
+
+   Monkey 0 :
+Starting items:
+Operation:   new=old + old * 2
+Test: divisible by 3
+If true: throw to monkey 1
+If false: throw to monkey 1   ",
+        )
+        .unwrap();
+        assert_eq!(monkeys.len(), 2);
+        assert_eq!(monkeys[0].id, 0);
+        assert!(monkeys[0].items.is_empty());
+        assert_eq!(monkeys[0].operation.eval(4), Some(12));
+        assert_eq!(monkeys[1].items, vec![79, 60]);
+        assert_eq!(monkeys[1].next, (0, 0));
+    }
+
+    fn error(input: &str) -> (Option<usize>, usize, String) {
+        let e = parse_input(input).unwrap_err();
+        (e.monkey, e.line, e.message)
+    }
+
+    const MONKEY_0: &str = "Monkey 0:
+  Starting items: 1
+  Operation: new = old + 1
+  Test: divisible by 2
+  If true: throw to monkey 1
+  If false: throw to monkey 1
+";
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            error(&(MONKEY_0.to_string() + "\nMonkey 1:\n  Operation: new = old ^ 2")),
+            (
+                Some(1),
+                9,
+                "invalid operation \"Operation: new = old ^ 2\"".to_string()
+            )
+        );
+        assert_eq!(
+            error(&MONKEY_0.replace("Test", "Check")),
+            (
+                Some(0),
+                4,
+                "unrecognised line \"Check: divisible by 2\"".to_string()
+            )
+        );
+        assert_eq!(
+            error(MONKEY_0),
+            (Some(0), 5, "cannot throw to monkey 1".to_string())
+        );
+        assert_eq!(
+            error(&MONKEY_0.replace("Monkey 0", "Monkey 1")),
+            (Some(1), 1, "no monkey 0".to_string())
+        );
+        assert_eq!(
+            error(&(MONKEY_0.to_string() + "\n" + MONKEY_0)),
+            (Some(0), 8, "id given twice".to_string())
+        );
+        assert_eq!(
+            error(&MONKEY_0.replace("  Test: divisible by 2\n", "")),
+            (Some(0), 1, "no test".to_string())
+        );
+        assert_eq!(
+            error(&MONKEY_0.replace("  If false", "  If true")),
+            (Some(0), 6, "if true given twice".to_string())
+        );
+        let monkey_1 = MONKEY_0
+            .replace("Monkey 0", "Monkey 1")
+            .replace("by 2", "by 4294967297")
+            .replace("monkey 1", "monkey 0");
+        assert_eq!(
+            error(&(MONKEY_0.replace("by 2", "by 4294967296") + "\n" + &monkey_1)),
+            (
+                Some(1),
+                8,
+                "divisors have no common multiple that fits".to_string()
+            )
+        );
+        assert_eq!(
+            error("  Test: divisible by 2"),
+            (None, 1, "expected a \"Monkey <id>:\" header".to_string())
+        );
+    }
+}
@@ -1,100 +1,31 @@
 use itertools::Itertools;
 
-#[derive(Debug, Clone)]
-enum Operation {
-    Add(usize),
-    Mult(usize),
-    Pow2,
-}
-
-#[derive(Debug, Clone)]
-pub struct Monkey {
-    items: Vec<usize>,
-    operation: Operation,
-    divisible_by: usize,
-    next: (usize, usize),
-    inspection: usize,
-}
-
-impl Monkey {
-    fn from_flat(input: &str) -> Self {
-        let last_value = |text: &str| -> usize {
-            text.split_ascii_whitespace()
-                .last()
-                .map(|x| x.parse().unwrap())
-                .unwrap()
-        };
-        let lines: Vec<&str> = input.split('\n').map(|l| l.trim()).collect();
-        let items = lines[1]
-            .split(": ")
-            .last()
-            .unwrap()
-            .split(", ")
-            .map(|n| n.parse().unwrap())
-            .collect();
-
-        let operation = lines[2]
-            .split("= ")
-            .last()
-            .map(|raw_op| {
-                if raw_op == "old * old" {
-                    Operation::Pow2
-                } else if let Some(x) = raw_op.split_once("+ ") {
-                    Operation::Add(x.1.parse().unwrap())
-                } else if let Some(x) = raw_op.split_once("* ") {
-                    Operation::Mult(x.1.parse().unwrap())
-                } else {
-                    unreachable!()
-                }
-            })
-            .unwrap();
-
-        let divisible_by = last_value(lines[3]);
-        let next = (last_value(lines[4]), last_value(lines[5]));
-
-        Monkey {
-            items,
-            operation,
-            divisible_by,
-            next,
-            inspection: 0,
-        }
-    }
-}
-
-fn parse_input(input: &str) -> Vec<Monkey> {
-    input.split("\n\n").map(Monkey::from_flat).collect()
-}
-
-fn round(monkeys: &mut Vec<Monkey>, worry_relief_fn: impl Fn(usize) -> usize) {
+use day11::{lcm, parse_input, Expr, Monkey};
+
+/// Runs one round, with `worry` giving an item's new worry level, or `None` if the monkey's
+/// operation cannot work it out.
+fn round(
+    monkeys: &mut [Monkey],
+    worry: impl Fn(&Expr, usize) -> Option<usize>,
+) -> Result<(), String> {
     for i in 0..monkeys.len() {
         let cm = monkeys[i].clone();
-        cm.items.iter().for_each(|item| {
+        for item in &cm.items {
             monkeys[i].inspection += 1;
-            let mut worry_level = match cm.operation {
-                Operation::Add(x) => item + x,
-                Operation::Mult(x) => item * x,
-                Operation::Pow2 => item * item,
-            };
+            let worry_level = worry(&cm.operation, *item)
+                .ok_or_else(|| format!("monkey {}: {} fails for {}", cm.id, cm.operation, item))?;
 
-            worry_level = worry_relief_fn(worry_level);
-
-            match worry_level % cm.divisible_by == 0 {
+            match worry_level.is_multiple_of(cm.divisible_by) {
                 true => monkeys[cm.next.0].items.push(worry_level),
                 false => monkeys[cm.next.1].items.push(worry_level),
             }
-        });
+        }
         monkeys[i].items.clear();
     }
+    Ok(())
 }
 
-fn part1(monkeys: &Vec<Monkey>) -> usize {
-    let mut monkeys = monkeys.clone();
-
-    (0..20).for_each(|_| {
-        round(&mut monkeys, |x| x / 3);
-    });
-
+fn monkey_business(monkeys: &[Monkey]) -> usize {
     monkeys
         .iter()
         .map(|m| m.inspection)
@@ -103,28 +34,54 @@ fn part1(monkeys: &Vec<Monkey>) -> usize {
         .product()
 }
 
-fn part2(monkeys: &Vec<Monkey>) -> usize {
-    let mut monkeys = monkeys.clone();
+fn part1(monkeys: &[Monkey]) -> Result<usize, String> {
+    let mut monkeys = monkeys.to_vec();
 
-    //relief number is calculated using Chinese remainder theorem [https://en.wikipedia.org/wiki/Chinese_remainder_theorem]
-    let relief_magic_no: usize = monkeys.iter().map(|m| m.divisible_by).product();
+    for _ in 0..20 {
+        round(&mut monkeys, |op, x| op.eval(x).map(|x| x / 3))?;
+    }
 
-     (0..10_000).for_each(|_| {
-        round(&mut monkeys, |x| x % relief_magic_no);
-    });
-    monkeys
-        .iter()
-        .map(|m| m.inspection)
-        .sorted_by(|a, b| b.cmp(a))
-        .take(2)
-        .product()
+    Ok(monkey_business(&monkeys))
+}
+
+/// Worry levels are only tested for divisibility, so they can be kept modulo the least common
+/// multiple of the divisors, as long as every operation only adds and multiplies.
+fn part2(monkeys: &[Monkey]) -> Result<usize, String> {
+    let mut monkeys = monkeys.to_vec();
+
+    if let Some(m) = monkeys.iter().find(|m| !m.operation.is_polynomial()) {
+        return Err(format!(
+            "monkey {}: {} cannot be worked out on remainders",
+            m.id, m.operation
+        ));
+    }
+    //relief number is calculated using Chinese remainder theorem [https://en.wikipedia.org/wiki/Chinese_remainder_theorem]
+    let relief_magic_no = monkeys.iter().try_fold(1, |acc, m| {
+        lcm(acc, m.divisible_by).ok_or_else(|| {
+            format!(
+                "monkey {}: divisors have no common multiple that fits",
+                m.id
+            )
+        })
+    })?;
+
+    for _ in 0..10_000 {
+        round(&mut monkeys, |op, x| op.eval_mod(x, relief_magic_no))?;
+    }
+    Ok(monkey_business(&monkeys))
 }
 
 fn main() {
     let input = include_str!("input").trim();
-    let monkeys = parse_input(input);
-    println!("Part1: {}", part1(&monkeys));
-    println!("Part2: {}", part2(&monkeys));
+    let monkeys = parse_input(input).unwrap_or_else(|e| panic!("{}", e));
+    println!(
+        "Part1: {}",
+        part1(&monkeys).unwrap_or_else(|e| panic!("{}", e))
+    );
+    println!(
+        "Part2: {}",
+        part2(&monkeys).unwrap_or_else(|e| panic!("{}", e))
+    );
 }
 
 #[cfg(test)]
@@ -135,39 +92,59 @@ mod tests {
 Starting items: 79, 98
 Operation: new = old * 19
 Test: divisible by 23
-    If true: throw to monke 2
-    If false: throw to monke 3
+    If true: throw to monkey 2
+    If false: throw to monkey 3
 
 Monkey 1:
 Starting items: 54, 65, 75, 74
 Operation: new = old + 6
 Test: divisible by 19
-    If true: throw to monke 2
-    If false: throw to monke 0
+    If true: throw to monkey 2
+    If false: throw to monkey 0
 
 Monkey 2:
 Starting items: 79, 60, 97
 Operation: new = old * old
 Test: divisible by 13
-    If true: throw to monke 1
-    If false: throw to monke 3
+    If true: throw to monkey 1
+    If false: throw to monkey 3
 
 Monkey 3:
 Starting items: 74
 Operation: new = old + 3
 Test: divisible by 17
-    If true: throw to monke 0
-    If false: throw to monke 1"#;
+    If true: throw to monkey 0
+    If false: throw to monkey 1"#;
 
     #[test]
     fn test_part1() {
-        let monkeys = parse_input(INPUT);
-        assert_eq!(part1(&monkeys), 10605);
+        let monkeys = parse_input(INPUT).unwrap();
+        assert_eq!(part1(&monkeys), Ok(10605));
     }
 
     #[test]
     fn test_part2() {
-        let monkeys = parse_input(INPUT);
-        assert_eq!(part2(&monkeys), 2713310158);
+        let monkeys = parse_input(INPUT).unwrap();
+        assert_eq!(part2(&monkeys), Ok(2713310158));
+    }
+
+    #[test]
+    fn test_extended_operations() {
+        let cubic = parse_input(&INPUT.replace("old * old", "old * old * old")).unwrap();
+        assert!(part1(&cubic).is_err_and(|e| e.starts_with("monkey 2: ((old * old) * old) fails")));
+        assert_eq!(part2(&cubic), Ok(2901438221));
+
+        let subtracting = parse_input(&INPUT.replace("old * old", "old * 2 - old / 2")).unwrap();
+        assert_eq!(part1(&subtracting), Ok(10706));
+        assert_eq!(
+            part2(&subtracting),
+            Err("monkey 2: ((old * 2) - (old / 2)) cannot be worked out on remainders".to_string())
+        );
+
+        let underflowing = parse_input(&INPUT.replace("old + 6", "old - 60")).unwrap();
+        assert_eq!(
+            part1(&underflowing),
+            Err("monkey 1: (old - 60) fails for 54".to_string())
+        );
     }
 }